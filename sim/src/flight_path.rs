@@ -0,0 +1,335 @@
+//! Curves for insects to fly along. Each path is a curve over `t` from 0 to 1, which is not
+//! evenly spread over its length, so [`ArcLengthPath`] measures it to fly it at a steady speed.
+//! None of them panic, whatever `t` or points they are given.
+
+use bevy_math::Vec3;
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// how many pieces an `ArcLengthPath` measures its path in
+pub const ARC_LENGTH_SAMPLES: usize = 128;
+
+pub trait FlightPath: Send + Sync {
+    /// the point at `t`, which is clamped to 0..=1
+    fn at(&self, t: f32) -> Vec3;
+}
+
+/// `t` clamped to 0..=1, NaN counts as 0
+fn clamp_t(t: f32) -> f32 {
+    if t.is_nan() {
+        0.0
+    } else {
+        t.clamp(0.0, 1.0)
+    }
+}
+
+/// a random number between `a` and `b`, also when they are equal or the wrong way round
+fn random_between(rng: &mut impl Rng, a: f32, b: f32) -> f32 {
+    a + (b - a) * rng.gen::<f32>()
+}
+
+/// a random point in the box spanned by `a` and `b`
+fn random_in_box(rng: &mut impl Rng, a: Vec3, b: Vec3) -> Vec3 {
+    Vec3::new(
+        random_between(rng, a.x, b.x),
+        random_between(rng, a.y, b.y),
+        random_between(rng, a.z, b.z),
+    )
+}
+
+/// a random direction, or zero in the rare case it can't make one
+fn random_direction(rng: &mut impl Rng) -> Vec3 {
+    Vec3::new(
+        rng.gen::<f32>() - 0.5,
+        rng.gen::<f32>() - 0.5,
+        rng.gen::<f32>() - 0.5,
+    )
+    .normalize_or_zero()
+}
+
+#[derive(Debug, Clone)]
+pub struct BezierCurve {
+    pub p0: Vec3,
+    pub p1: Vec3,
+    pub p2: Vec3,
+    pub p3: Vec3,
+}
+
+impl BezierCurve {
+    pub fn new(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3) -> Self {
+        BezierCurve { p0, p1, p2, p3 }
+    }
+
+    /// A curve from `p0` to `p3` with its handles somewhere in the box between them, the one
+    /// nearer the camera second.
+    pub fn random_from_endpoints(p0: Vec3, p3: Vec3, rng: &mut impl Rng) -> Self {
+        let p1 = random_in_box(rng, p0, p3);
+        let p2 = random_in_box(rng, p0, p3);
+        if p2.z < p1.z {
+            BezierCurve::new(p0, p2, p1, p3)
+        } else {
+            BezierCurve::new(p0, p1, p2, p3)
+        }
+    }
+}
+
+impl FlightPath for BezierCurve {
+    fn at(&self, t: f32) -> Vec3 {
+        let t = clamp_t(t);
+        (1.0 - t).powi(3) * self.p0
+            + 3.0 * (1.0 - t) * (1.0 - t) * t * self.p1
+            + 3.0 * (1.0 - t) * t * t * self.p2
+            + t * t * t * self.p3
+    }
+}
+
+/// A smooth curve through every one of its points
+#[derive(Debug, Clone)]
+pub struct CatmullRom {
+    pub points: Vec<Vec3>,
+}
+
+impl CatmullRom {
+    pub fn new(points: Vec<Vec3>) -> Self {
+        CatmullRom { points }
+    }
+
+    /// From `start` to `end` through `waypoints` points spread evenly between them, each moved
+    /// up to `spread` in a random direction.
+    pub fn random_through(
+        start: Vec3,
+        end: Vec3,
+        waypoints: usize,
+        spread: f32,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let mut points = vec![start];
+        for i in 1..=waypoints {
+            let along = i as f32 / (waypoints + 1) as f32;
+            let offset = random_direction(rng) * random_between(rng, 0.0, spread);
+            points.push(start.lerp(end, along) + offset);
+        }
+        points.push(end);
+        CatmullRom::new(points)
+    }
+
+    /// Flies from `start` to circle `loops` times around `centre` at `radius`, level with it,
+    /// then on to `end`.
+    pub fn hovering(start: Vec3, centre: Vec3, radius: f32, loops: u32, end: Vec3) -> Self {
+        const POINTS_PER_LOOP: u32 = 8;
+        let mut points = vec![start];
+        for i in 0..=loops * POINTS_PER_LOOP {
+            let angle = 2.0 * PI * i as f32 / POINTS_PER_LOOP as f32;
+            points.push(centre + Vec3::new(angle.cos(), 0.0, angle.sin()) * radius);
+        }
+        points.push(end);
+        CatmullRom::new(points)
+    }
+}
+
+impl FlightPath for CatmullRom {
+    fn at(&self, t: f32) -> Vec3 {
+        let n = self.points.len();
+        if n < 2 {
+            return self.points.first().copied().unwrap_or(Vec3::ZERO);
+        }
+
+        let segments = n - 1;
+        let scaled = clamp_t(t) * segments as f32;
+        let i = (scaled as usize).min(segments - 1);
+        let u = scaled - i as f32;
+
+        let p0 = self.points[i.saturating_sub(1)];
+        let p1 = self.points[i];
+        let p2 = self.points[i + 1];
+        let p3 = self.points[(i + 2).min(n - 1)];
+
+        0.5 * (2.0 * p1
+            + (p2 - p0) * u
+            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * u * u
+            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * u * u * u)
+    }
+}
+
+/// A straight line with a sideways wave on top
+#[derive(Debug, Clone)]
+pub struct ZigZag {
+    pub start: Vec3,
+    pub end: Vec3,
+    /// unit direction the wave swings in
+    pub side: Vec3,
+    /// how far the wave swings either way
+    pub amplitude: f32,
+    /// how many full waves there are between `start` and `end`
+    pub waves: f32,
+}
+
+impl ZigZag {
+    /// the wave swings level with the ground, or along x when flying straight up or down
+    pub fn new(start: Vec3, end: Vec3, amplitude: f32, waves: f32) -> Self {
+        let side = (end - start).cross(Vec3::Y).normalize_or_zero();
+        ZigZag {
+            start,
+            end,
+            side: if side == Vec3::ZERO { Vec3::X } else { side },
+            amplitude,
+            waves,
+        }
+    }
+}
+
+impl FlightPath for ZigZag {
+    fn at(&self, t: f32) -> Vec3 {
+        let t = clamp_t(t);
+        self.start.lerp(self.end, t)
+            + self.side * self.amplitude * (2.0 * PI * self.waves * t).sin()
+    }
+}
+
+/// Straight lines between its points
+#[derive(Debug, Clone)]
+pub struct Polyline {
+    pub points: Vec<Vec3>,
+}
+
+impl Polyline {
+    pub fn new(points: Vec<Vec3>) -> Self {
+        Polyline { points }
+    }
+
+    /// The path of something steering towards `goal` with a bit of random wandering, like a
+    /// boid seeking a target. It always ends at `goal`.
+    pub fn steered(start: Vec3, goal: Vec3, rng: &mut impl Rng) -> Self {
+        const STEP: f32 = 0.1;
+        const MAX_STEPS: usize = 500;
+        const SPEED: f32 = 1.0;
+        const MAX_FORCE: f32 = 2.0;
+        const WANDER: f32 = 3.0;
+
+        let mut position = start;
+        let mut velocity = (goal - start).normalize_or_zero() * SPEED;
+        let mut points = vec![start];
+        for _ in 0..MAX_STEPS {
+            if position.distance(goal) <= SPEED * STEP {
+                break;
+            }
+            let desired = (goal - position).normalize_or_zero() * SPEED;
+            let steer =
+                (desired - velocity + random_direction(rng) * WANDER).clamp_length_max(MAX_FORCE);
+            velocity = (velocity + steer * STEP).clamp_length_max(SPEED);
+            position += velocity * STEP;
+            points.push(position);
+        }
+        points.push(goal);
+        Polyline::new(points)
+    }
+}
+
+impl FlightPath for Polyline {
+    fn at(&self, t: f32) -> Vec3 {
+        let n = self.points.len();
+        if n < 2 {
+            return self.points.first().copied().unwrap_or(Vec3::ZERO);
+        }
+
+        let scaled = clamp_t(t) * (n - 1) as f32;
+        let i = (scaled as usize).min(n - 2);
+        self.points[i].lerp(self.points[i + 1], scaled - i as f32)
+    }
+}
+
+/// A path measured along its length, so it can be flown at a steady speed
+pub struct ArcLengthPath {
+    path: Box<dyn FlightPath>,
+    /// distance along the path at `t = i / ARC_LENGTH_SAMPLES`
+    distances: Vec<f32>,
+}
+
+impl ArcLengthPath {
+    pub fn new(path: impl FlightPath + 'static) -> Self {
+        let mut distances = Vec::with_capacity(ARC_LENGTH_SAMPLES + 1);
+        let mut total = 0.0;
+        let mut previous = path.at(0.0);
+        distances.push(0.0);
+        for i in 1..=ARC_LENGTH_SAMPLES {
+            let point = path.at(i as f32 / ARC_LENGTH_SAMPLES as f32);
+            let step = point.distance(previous);
+            // a broken point would make the length NaN, don't count it
+            if step.is_finite() {
+                total += step;
+            }
+            distances.push(total);
+            previous = point;
+        }
+
+        ArcLengthPath {
+            path: Box::new(path),
+            distances,
+        }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+
+    /// the path's `t` at `distance` along it
+    fn t_at(&self, distance: f32) -> f32 {
+        let distance = distance.max(0.0).min(self.length());
+        let i = self
+            .distances
+            .partition_point(|d| *d < distance)
+            .clamp(1, ARC_LENGTH_SAMPLES);
+        let (before, after) = (self.distances[i - 1], self.distances[i]);
+        let within = if after > before {
+            (distance - before) / (after - before)
+        } else {
+            0.0
+        };
+        (i as f32 - 1.0 + within) / ARC_LENGTH_SAMPLES as f32
+    }
+
+    /// the point `distance` along the path, the ends when past them
+    pub fn position(&self, distance: f32) -> Vec3 {
+        self.path.at(self.t_at(distance))
+    }
+
+    /// which way the path goes at `distance` along it, zero where it doesn't go anywhere
+    pub fn direction(&self, distance: f32) -> Vec3 {
+        let step = self.length() / ARC_LENGTH_SAMPLES as f32;
+        (self.position(distance + step) - self.position(distance - step)).normalize_or_zero()
+    }
+}
+
+/// A light insects are drawn to, `intensity` 1 is as bright as it usually gets
+#[derive(Debug, Clone, Copy)]
+pub struct Lure {
+    pub position: Vec3,
+    pub intensity: f32,
+}
+
+/// Which way an insect at `position` is drawn by the lures within `range`, each pulling by its
+/// intensity and less the further away it is. Far from a lure the pull points at it, at
+/// `orbit_radius` it turns to circle the lure level with it. Zero when no lure is in range.
+pub fn lure_pull(position: Vec3, lures: &[Lure], range: f32, orbit_radius: f32) -> Vec3 {
+    let mut pull = Vec3::ZERO;
+    for lure in lures {
+        let offset = lure.position - position;
+        let distance = offset.length();
+        if distance.is_nan() || distance >= range {
+            continue;
+        }
+
+        let weight = lure.intensity.max(0.0) * (1.0 - distance / range);
+        let towards = offset.normalize_or_zero();
+        let around = towards.cross(Vec3::Y).normalize_or_zero();
+        // 1 at twice the orbit radius and beyond, 0 on the orbit, -1 at the lure
+        let closing = if orbit_radius > 0.0 {
+            ((distance - orbit_radius) / orbit_radius).clamp(-1.0, 1.0)
+        } else {
+            1.0
+        };
+        let direction = (towards * closing + around * (1.0 - closing.abs())).normalize_or_zero();
+        pull += direction * weight;
+    }
+    pull
+}
@@ -0,0 +1,116 @@
+//! The best runs on this machine, ranked by how long the spider survived and by how many
+//! insects it ate.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Bumped whenever the layout of [`HighScoreTable`] changes.
+pub const HIGH_SCORE_VERSION: u32 = 1;
+
+/// How many runs are kept for each ranking.
+pub const MAX_HIGH_SCORES: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    /// seconds since the unix epoch when the run ended
+    pub date: u64,
+    pub survival_time: f32,
+    pub insects_eaten: u32,
+    pub silk_used: f32,
+    pub score: u32,
+}
+
+impl HighScoreEntry {
+    /// day the run ended as YYYY-MM-DD, in UTC
+    pub fn date_string(&self) -> String {
+        let (year, month, day) = civil_from_days((self.date / 86400) as i64);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    fn cmp_survival_time(&self, other: &Self) -> Ordering {
+        other
+            .survival_time
+            .total_cmp(&self.survival_time)
+            .then(other.insects_eaten.cmp(&self.insects_eaten))
+    }
+
+    fn cmp_insects_eaten(&self, other: &Self) -> Ordering {
+        other
+            .insects_eaten
+            .cmp(&self.insects_eaten)
+            .then(other.survival_time.total_cmp(&self.survival_time))
+    }
+}
+
+/// The best runs on this machine. Runs are kept while they are in the top [`MAX_HIGH_SCORES`]
+/// of either ranking.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScoreTable {
+    pub version: u32,
+    pub entries: Vec<HighScoreEntry>,
+}
+
+impl Default for HighScoreTable {
+    fn default() -> Self {
+        HighScoreTable {
+            version: HIGH_SCORE_VERSION,
+            entries: vec![],
+        }
+    }
+}
+
+impl HighScoreTable {
+    /// best runs first, longest survival first
+    pub fn by_survival_time(&self) -> Vec<&HighScoreEntry> {
+        let mut entries: Vec<&HighScoreEntry> = self.entries.iter().collect();
+        entries.sort_by(|a, b| a.cmp_survival_time(b));
+        entries.truncate(MAX_HIGH_SCORES);
+        entries
+    }
+
+    /// best runs first, most insects eaten first
+    pub fn by_insects_eaten(&self) -> Vec<&HighScoreEntry> {
+        let mut entries: Vec<&HighScoreEntry> = self.entries.iter().collect();
+        entries.sort_by(|a, b| a.cmp_insects_eaten(b));
+        entries.truncate(MAX_HIGH_SCORES);
+        entries
+    }
+
+    /// adds the run and drops runs that no longer place in either ranking, returns whether the
+    /// new run was kept
+    pub fn insert(&mut self, entry: HighScoreEntry) -> bool {
+        self.entries.push(entry);
+        let new_index = self.entries.len() - 1;
+
+        let mut keep = vec![false; self.entries.len()];
+        let mut indices: Vec<usize> = (0..self.entries.len()).collect();
+        indices.sort_by(|a, b| self.entries[*a].cmp_survival_time(&self.entries[*b]));
+        for i in indices.iter().take(MAX_HIGH_SCORES) {
+            keep[*i] = true;
+        }
+        indices.sort_by(|a, b| self.entries[*a].cmp_insects_eaten(&self.entries[*b]));
+        for i in indices.iter().take(MAX_HIGH_SCORES) {
+            keep[*i] = true;
+        }
+
+        let kept = keep[new_index];
+        let mut keep = keep.into_iter();
+        self.entries.retain(|_| keep.next().unwrap());
+        kept
+    }
+}
+
+/// year, month and day of a day count since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
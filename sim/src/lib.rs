@@ -0,0 +1,7 @@
+//! Parts of the game that don't need a running `App`, so they can be used and tested on their
+//! own. The game binary wraps them in plugins and systems.
+
+pub mod flight_path;
+pub mod high_score;
+pub mod swarm;
+pub mod web_sim;
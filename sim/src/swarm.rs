@@ -0,0 +1,119 @@
+//! Flocking for swarms of insects. Each one is a boid that keeps its distance from the others,
+//! lines up with them and stays with the group while heading for a goal. A spatial hash keeps
+//! the neighbour lookups cheap however big the swarm gets.
+
+use bevy_math::{IVec3, Vec3};
+use std::collections::HashMap;
+
+/// Points sorted into a grid of cubes, for finding the ones near a position without looking at
+/// all of them
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<IVec3, Vec<usize>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32, positions: &[Vec3]) -> Self {
+        let mut hash = SpatialHash {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+        };
+        for (i, position) in positions.iter().enumerate() {
+            let cell = hash.cell(*position);
+            hash.cells.entry(cell).or_default().push(i);
+        }
+        hash
+    }
+
+    fn cell(&self, position: Vec3) -> IVec3 {
+        (position / self.cell_size).floor().as_ivec3()
+    }
+
+    /// Indices of the points in the cells within `radius` of `position`. Some may be a little
+    /// further away than `radius`, none closer are left out.
+    pub fn near(&self, position: Vec3, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let reach = (radius.max(0.0) / self.cell_size).ceil() as i32;
+        let centre = self.cell(position);
+        (-reach..=reach)
+            .flat_map(move |x| {
+                (-reach..=reach)
+                    .flat_map(move |y| (-reach..=reach).map(move |z| IVec3::new(x, y, z)))
+            })
+            .filter_map(move |offset| self.cells.get(&(centre + offset)))
+            .flatten()
+            .copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Boid {
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+/// How boids steer, the weights scale each rule's pull
+#[derive(Debug, Clone)]
+pub struct BoidRules {
+    /// boids closer than this push each other apart
+    pub separation_radius: f32,
+    /// boids closer than this line up and bunch together
+    pub neighbour_radius: f32,
+    pub separation: f32,
+    pub alignment: f32,
+    pub cohesion: f32,
+    /// how hard they steer for the goal
+    pub goal: f32,
+    pub max_speed: f32,
+    pub max_force: f32,
+}
+
+/// Moves the boids on by `dt` seconds, each steering by the rules from its neighbours and
+/// towards `goal`.
+pub fn step_boids(boids: &mut [Boid], goal: Vec3, rules: &BoidRules, dt: f32) {
+    let positions: Vec<Vec3> = boids.iter().map(|boid| boid.position).collect();
+    let hash = SpatialHash::new(rules.neighbour_radius, &positions);
+
+    let forces: Vec<Vec3> = boids
+        .iter()
+        .enumerate()
+        .map(|(i, boid)| {
+            let mut separation = Vec3::ZERO;
+            let mut heading = Vec3::ZERO;
+            let mut centre = Vec3::ZERO;
+            let mut neighbours = 0;
+            for j in hash.near(boid.position, rules.neighbour_radius) {
+                if j == i {
+                    continue;
+                }
+                let other = &boids[j];
+                let away = boid.position - other.position;
+                let distance = away.length();
+                if distance >= rules.neighbour_radius {
+                    continue;
+                }
+                if distance < rules.separation_radius {
+                    separation +=
+                        away.normalize_or_zero() * (1.0 - distance / rules.separation_radius);
+                }
+                heading += other.velocity;
+                centre += other.position;
+                neighbours += 1;
+            }
+
+            let mut force = separation * rules.separation;
+            if neighbours > 0 {
+                let neighbours = neighbours as f32;
+                force += (heading / neighbours - boid.velocity) * rules.alignment;
+                force += (centre / neighbours - boid.position) * rules.cohesion;
+            }
+            let towards_goal = (goal - boid.position).normalize_or_zero() * rules.max_speed;
+            force += (towards_goal - boid.velocity) * rules.goal;
+            force.clamp_length_max(rules.max_force)
+        })
+        .collect();
+
+    for (boid, force) in boids.iter_mut().zip(forces) {
+        boid.velocity = (boid.velocity + force * dt).clamp_length_max(rules.max_speed);
+        boid.position += boid.velocity * dt;
+    }
+}
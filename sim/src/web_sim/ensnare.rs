@@ -0,0 +1,152 @@
+use super::spring::Spring;
+use super::{SpringKey, Web};
+use bevy_math::Vec3;
+use tracing::error;
+
+#[derive(Debug, Clone)]
+pub struct EnsnaredEntity<T> {
+    /// the entity that is snared in the web
+    pub entity: T,
+    /// the position along the spring at which it's ensnared.
+    ///  ranges from 0 (first particle) -> 1 (second particle)
+    pub snare_position: f32,
+    pub mass: f32,
+    pub rotation: f32,
+    pub lerp_rotation: f32,
+    pub done_ensnaring: bool,
+}
+
+impl<T> EnsnaredEntity<T> {
+    pub fn snare_position_from_world_space(
+        snare_position_world_space: Vec3,
+        first_particle_position: Vec3,
+        second_particle_position: Vec3,
+    ) -> f32 {
+        (snare_position_world_space - first_particle_position).length()
+            / (second_particle_position - first_particle_position).length()
+    }
+
+    pub fn from_snare_position_world_space(
+        entity: T,
+        mass: f32,
+        snare_position_world_space: Vec3,
+        first_particle_position: Vec3,
+        second_particle_position: Vec3,
+    ) -> Self {
+        let snare_position = Self::snare_position_from_world_space(
+            snare_position_world_space,
+            first_particle_position,
+            second_particle_position,
+        );
+        if !(0.0..=1.0).contains(&snare_position) {
+            error!("snare_position={snare_position} is off the spring");
+        }
+        let snare_position = snare_position.clamp(0.0, 1.0);
+
+        EnsnaredEntity {
+            entity,
+            snare_position,
+            mass,
+            rotation: 0.0,
+            lerp_rotation: 0.0,
+            done_ensnaring: false,
+        }
+    }
+    pub fn snare_position_world_space(
+        &self,
+        first_particle_position: Vec3,
+        second_particle_position: Vec3,
+    ) -> Vec3 {
+        ((1.0 - self.snare_position) * first_particle_position)
+            + (self.snare_position * second_particle_position)
+    }
+}
+
+impl<T> Web<T> {
+    /// Pushes a spring at `t` for `duration` seconds, like something caught there tugging on
+    /// it. The push is shared between the two particles by how close each is to `t`, and adds to
+    /// any push they are already getting.
+    pub fn push_at(&mut self, spring_index: SpringKey, t: f32, impulse: Vec3, duration: f32) {
+        let Some(spring) = self.springs.get(spring_index) else {
+            return;
+        };
+        let t = t.clamp(0.0, 1.0);
+        for (particle, share) in [(spring.first_index, 1.0 - t), (spring.second_index, t)] {
+            let particle = &mut self.particles[particle];
+            particle.impulse += impulse * share;
+            particle.impulse_duration = particle.impulse_duration.max(duration);
+        }
+    }
+
+    /// Whether pulling `extra_strain` further on a spring, on top of how far it is already
+    /// stretched, would tear it. Strands that are already taut tear first.
+    pub fn would_tear(&self, spring_index: SpringKey, extra_strain: f32) -> bool {
+        let Some(spring) = self.springs.get(spring_index) else {
+            return false;
+        };
+        spring.rest_length > f32::EPSILON
+            && spring.get_strain(self) + extra_strain > spring.max_strain
+    }
+}
+
+pub fn split_ensnared_entities_for_spring_split<T: Copy>(
+    web: &Web<T>,
+    old_spring: &Spring<T>,
+    split_position: Vec3,
+) -> (Vec<EnsnaredEntity<T>>, Vec<EnsnaredEntity<T>>) {
+    let mut new_particle_t = EnsnaredEntity::<T>::snare_position_from_world_space(
+        split_position,
+        web.particles[old_spring.first_index].position,
+        web.particles[old_spring.second_index].position,
+    );
+
+    if !(0.0..=1.0).contains(&new_particle_t) {
+        error!("new_particle_t={new_particle_t}");
+    }
+
+    new_particle_t = new_particle_t.clamp(0.0, 1.0);
+
+    let new_spring_1_ensnared_entities = old_spring
+        .ensnared_entities
+        .iter()
+        .filter(|ensnared| ensnared.snare_position <= new_particle_t)
+        .map(|ensnared| {
+            let snare_position_world_space = ensnared.snare_position_world_space(
+                web.particles[old_spring.first_index].position,
+                web.particles[old_spring.second_index].position,
+            );
+
+            EnsnaredEntity::from_snare_position_world_space(
+                ensnared.entity,
+                ensnared.mass,
+                snare_position_world_space,
+                web.particles[old_spring.first_index].position,
+                split_position,
+            )
+        })
+        .collect();
+    let new_spring_2_ensnared_entities = old_spring
+        .ensnared_entities
+        .iter()
+        .filter(|ensnared| ensnared.snare_position > new_particle_t)
+        .map(|ensnared| {
+            let snare_position_world_space = ensnared.snare_position_world_space(
+                web.particles[old_spring.first_index].position,
+                web.particles[old_spring.second_index].position,
+            );
+
+            EnsnaredEntity::from_snare_position_world_space(
+                ensnared.entity,
+                ensnared.mass,
+                snare_position_world_space,
+                split_position,
+                web.particles[old_spring.second_index].position,
+            )
+        })
+        .collect();
+
+    (
+        new_spring_1_ensnared_entities,
+        new_spring_2_ensnared_entities,
+    )
+}
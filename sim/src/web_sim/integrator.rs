@@ -0,0 +1,137 @@
+use super::{accumulate_forces, Particle, Web};
+use bevy_math::Vec3;
+use serde::{Deserialize, Serialize};
+
+/// Particles lighter than this are not moved by forces. A particle only gets mass from the
+/// springs attached to it, so one left over after a split or an aborted strand has none.
+pub const MIN_PARTICLE_MASS: f32 = 1e-6;
+
+pub const XPBD_ITERATIONS: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Integrator {
+    /// velocity first, then position using the new velocity. cheapest, but stiff springs need
+    /// a tiny substep to stay stable
+    #[default]
+    SemiImplicitEuler,
+    /// velocity verlet, evaluates the forces twice per step
+    Verlet,
+    /// extended position based dynamics, springs are solved as compliant distance constraints
+    /// with a compliance of `1 / stiffness`. spring damping is ignored, air damping still applies
+    Xpbd { iterations: u32 },
+}
+
+impl Integrator {
+    pub fn next(self) -> Self {
+        match self {
+            Integrator::SemiImplicitEuler => Integrator::Verlet,
+            Integrator::Verlet => Integrator::Xpbd {
+                iterations: XPBD_ITERATIONS,
+            },
+            Integrator::Xpbd { .. } => Integrator::SemiImplicitEuler,
+        }
+    }
+}
+
+fn is_free(particle: &Particle) -> bool {
+    !particle.pinned && particle.mass > MIN_PARTICLE_MASS
+}
+
+fn acceleration(particle: &Particle) -> Vec3 {
+    if is_free(particle) {
+        particle.force / particle.mass
+    } else {
+        Vec3::ZERO
+    }
+}
+
+fn inverse_mass(particle: &Particle) -> f32 {
+    if is_free(particle) {
+        1.0 / particle.mass
+    } else {
+        0.0
+    }
+}
+
+pub fn semi_implicit_euler<T>(web: &mut Web<T>, air_damping: f32, h: f32) {
+    accumulate_forces(web, air_damping, h, true);
+
+    for particle in web.particles.values_mut() {
+        if !is_free(particle) {
+            continue;
+        }
+
+        particle.velocity += particle.force / particle.mass * h;
+        particle.position += particle.velocity * h;
+    }
+}
+
+pub fn velocity_verlet<T>(web: &mut Web<T>, air_damping: f32, h: f32) {
+    accumulate_forces(web, air_damping, h, true);
+
+    let old_accelerations: Vec<Vec3> = web.particles.values().map(acceleration).collect();
+    for (particle, old_acceleration) in web.particles.values_mut().zip(&old_accelerations) {
+        if !is_free(particle) {
+            continue;
+        }
+
+        particle.position += particle.velocity * h + 0.5 * *old_acceleration * h * h;
+    }
+
+    accumulate_forces(web, air_damping, h, true);
+
+    for (particle, old_acceleration) in web.particles.values_mut().zip(&old_accelerations) {
+        if !is_free(particle) {
+            continue;
+        }
+
+        particle.velocity += 0.5 * (*old_acceleration + acceleration(particle)) * h;
+    }
+}
+
+pub fn xpbd<T>(web: &mut Web<T>, air_damping: f32, h: f32, iterations: u32) {
+    accumulate_forces(web, air_damping, h, false);
+
+    let previous_positions: Vec<Vec3> = web.particles.values().map(|p| p.position).collect();
+    for particle in web.particles.values_mut() {
+        if !is_free(particle) {
+            continue;
+        }
+
+        particle.velocity += particle.force / particle.mass * h;
+        particle.position += particle.velocity * h;
+    }
+
+    let mut lambdas = vec![0.0; web.springs.len()];
+    for _ in 0..iterations {
+        for (j, spring) in web.springs.values().enumerate() {
+            let p1 = &web.particles[spring.first_index];
+            let p2 = &web.particles[spring.second_index];
+            let w1 = inverse_mass(p1);
+            let w2 = inverse_mass(p2);
+
+            let p_diff = p1.position - p2.position;
+            let cur_len = p_diff.length();
+            if w1 + w2 == 0.0 || cur_len < f32::EPSILON || spring.stiffness <= 0.0 {
+                continue;
+            }
+
+            let c = cur_len - spring.rest_length;
+            let compliance = 1.0 / (spring.stiffness * h * h);
+            let δλ = (-c - compliance * lambdas[j]) / (w1 + w2 + compliance);
+            lambdas[j] += δλ;
+
+            let correction = p_diff / cur_len * δλ;
+            web.particles[spring.first_index].position += w1 * correction;
+            web.particles[spring.second_index].position -= w2 * correction;
+        }
+    }
+
+    for (particle, previous) in web.particles.values_mut().zip(previous_positions) {
+        if !is_free(particle) {
+            continue;
+        }
+
+        particle.velocity = (particle.position - previous) / h;
+    }
+}
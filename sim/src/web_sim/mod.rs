@@ -0,0 +1,414 @@
+//! Spring-mass simulation of the web, without any ECS. `T` is whatever the caller uses to
+//! identify things caught in the web, the game uses `Entity`.
+
+pub mod ensnare;
+pub mod integrator;
+pub mod path;
+pub mod spring;
+pub mod water;
+
+use bevy_math::Vec3;
+use ensnare::{split_ensnared_entities_for_spring_split, EnsnaredEntity};
+use integrator::Integrator;
+use slotmap::{new_key_type, SecondaryMap, SlotMap};
+use spring::Spring;
+use std::collections::{HashMap, HashSet};
+
+new_key_type! {
+    /// Handle to a particle of a `Web`, it stays valid while other particles are added or
+    /// removed and stops resolving once its own particle is removed
+    pub struct ParticleKey;
+    /// Handle to a spring of a `Web`, see `ParticleKey`
+    pub struct SpringKey;
+}
+
+pub const GRAVITY: f32 = 9.81;
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub force: Vec3,
+    pub impulse: Vec3,
+    pub impulse_duration: f32,
+    pub mass: f32,
+    pub pinned: bool,
+}
+
+impl Particle {
+    /// a particle at rest, it gets its mass from the springs attached to it
+    pub fn at(position: Vec3, pinned: bool) -> Self {
+        Particle {
+            position,
+            velocity: Vec3::ZERO,
+            force: Vec3::ZERO,
+            impulse: Vec3::ZERO,
+            impulse_duration: 0.0,
+            mass: 0.0,
+            pinned,
+        }
+    }
+}
+
+/// A spring that was stretched too far and snapped
+#[derive(Debug, Clone)]
+pub struct SnappedSpring<T> {
+    pub position: Vec3,
+    /// whatever was ensnared on the spring and is now free
+    pub released: Vec<T>,
+}
+
+/// A spring that `split_spring` or `cut_spring` replaced with two shorter ones
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpringSplit {
+    pub old: SpringKey,
+    /// where the old spring was split, 0 at its first particle and 1 at its second
+    pub t: f32,
+    /// the half from the old first particle to the split
+    pub first: SpringKey,
+    /// the half from the split to the old second particle
+    pub second: SpringKey,
+    /// whether the halves still meet at a particle, false for a cut
+    pub joined: bool,
+}
+
+impl SpringSplit {
+    /// The half a point `t` along the old spring is on now, and how far along that half.
+    pub fn remap(&self, t: f32) -> (SpringKey, f32) {
+        if t <= self.t && self.t > 0.0 {
+            (self.first, (t / self.t).clamp(0.0, 1.0))
+        } else if self.t < 1.0 {
+            (self.second, ((t - self.t) / (1.0 - self.t)).clamp(0.0, 1.0))
+        } else {
+            (self.first, 1.0)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Web<T> {
+    pub particles: SlotMap<ParticleKey, Particle>,
+    pub springs: SlotMap<SpringKey, Spring<T>>,
+    pub mass_per_unit_length: f32,
+    pub integrator: Integrator,
+    /// mass of water sitting on particles, see `wet`
+    pub water: SecondaryMap<ParticleKey, f32>,
+    /// set when a spring is cut, so part of the web may have come loose, `compact` clears it
+    pub needs_compact: bool,
+    /// Springs split or cut, oldest first, for moving positions on them onto the new halves
+    /// with `follow_splits`. Whoever follows them clears it.
+    pub splits: Vec<SpringSplit>,
+}
+
+impl<T> Default for Web<T> {
+    fn default() -> Self {
+        Web {
+            particles: SlotMap::with_key(),
+            springs: SlotMap::with_key(),
+            mass_per_unit_length: 0.1,
+            integrator: Default::default(),
+            water: SecondaryMap::new(),
+            needs_compact: false,
+            splits: vec![],
+        }
+    }
+}
+
+impl<T: Copy> Web<T> {
+    /// Cuts a spring at `t` (0 at the first particle, 1 at the second), leaving two loose ends.
+    /// The entities ensnared on the spring are taken off the web and returned.
+    pub fn cut_spring(&mut self, spring_index: SpringKey, t: f32) -> Vec<EnsnaredEntity<T>> {
+        let Some(old_spring) = self.springs.remove(spring_index) else {
+            return vec![];
+        };
+        self.needs_compact = true;
+        let p1 = self.particles[old_spring.first_index].position;
+        let p2 = self.particles[old_spring.second_index].position;
+        let cut_position = p2 * t + p1 * (1.0 - t);
+
+        let loose_end = || Particle {
+            impulse: Vec3::new(0.0, 0.0, 1.0) * 10000.0,
+            impulse_duration: 0.1,
+            ..Particle::at(cut_position, false)
+        };
+        let new_particle_1 = self.particles.insert(loose_end());
+        let new_particle_2 = self.particles.insert(loose_end());
+
+        let first = self.springs.insert(Spring {
+            first_index: old_spring.first_index,
+            second_index: new_particle_1,
+            rest_length: old_spring.rest_length * t,
+            ensnared_entities: vec![],
+            ..old_spring
+        });
+        let second = self.springs.insert(Spring {
+            first_index: new_particle_2,
+            second_index: old_spring.second_index,
+            rest_length: old_spring.rest_length * (1.0 - t),
+            ensnared_entities: vec![],
+            ..old_spring
+        });
+        self.splits.push(SpringSplit {
+            old: spring_index,
+            t,
+            first,
+            second,
+            joined: false,
+        });
+
+        old_spring.ensnared_entities
+    }
+
+    /// Snaps every spring stretched past its `max_strain` at its midpoint. Springs created by a
+    /// snap are not checked again until the next call.
+    pub fn snap_overstretched_springs(&mut self) -> Vec<SnappedSpring<T>> {
+        let mut snapped = vec![];
+
+        let keys: Vec<SpringKey> = self.springs.keys().collect();
+        for i in keys {
+            let spring = &self.springs[i];
+            let p1 = self.particles[spring.first_index].position;
+            let p2 = self.particles[spring.second_index].position;
+            if spring.rest_length <= f32::EPSILON || spring.get_strain(self) <= spring.max_strain {
+                continue;
+            }
+
+            let released = self.cut_spring(i, 0.5);
+            snapped.push(SnappedSpring {
+                position: (p1 + p2) / 2.0,
+                released: released.iter().map(|ensnared| ensnared.entity).collect(),
+            });
+        }
+
+        snapped
+    }
+
+    /// Removes springs that can no longer reach a pinned particle, then every particle that is
+    /// not attached to a spring anymore. Returns the entities that were ensnared on the removed
+    /// springs.
+    pub fn compact(&mut self) -> Vec<T> {
+        self.needs_compact = false;
+        let mut particle_springs: HashMap<ParticleKey, Vec<SpringKey>> = HashMap::new();
+        for (j, spring) in self.springs.iter() {
+            particle_springs
+                .entry(spring.first_index)
+                .or_default()
+                .push(j);
+            particle_springs
+                .entry(spring.second_index)
+                .or_default()
+                .push(j);
+        }
+
+        // flood fill from the anchors, anything not reached is a loose fragment
+        let mut to_visit: Vec<ParticleKey> = self
+            .particles
+            .iter()
+            .filter(|(_, particle)| particle.pinned)
+            .map(|(i, _)| i)
+            .collect();
+        let mut anchored: HashSet<ParticleKey> = to_visit.iter().copied().collect();
+        while let Some(i) = to_visit.pop() {
+            for j in particle_springs.get(&i).into_iter().flatten() {
+                let spring = &self.springs[*j];
+                for other in [spring.first_index, spring.second_index] {
+                    if anchored.insert(other) {
+                        to_visit.push(other);
+                    }
+                }
+            }
+        }
+
+        let mut released = vec![];
+        self.springs.retain(|_, spring| {
+            let keep = anchored.contains(&spring.first_index);
+            if !keep {
+                released.extend(spring.ensnared_entities.iter().map(|e| e.entity));
+            }
+            keep
+        });
+
+        let springs = &self.springs;
+        self.particles.retain(|i, _| {
+            particle_springs
+                .get(&i)
+                .is_some_and(|js| js.iter().any(|j| springs.contains_key(*j)))
+        });
+
+        released
+    }
+
+    /// Splits a spring in two at `position`, returns the particle joining the two halves. Both
+    /// halves keep the stiffness and damping of the old spring.
+    pub fn split_spring(&mut self, spring_index: SpringKey, position: Vec3) -> ParticleKey {
+        let new_particle = self.particles.insert(Particle::at(position, false));
+
+        let old_spring: Spring<T> = self.springs.remove(spring_index).unwrap();
+        let t = (position - self.particles[old_spring.first_index].position).length()
+            / (self.particles[old_spring.second_index].position
+                - self.particles[old_spring.first_index].position)
+                .length();
+
+        let (new_spring_1_ensnared_entities, new_spring_2_ensnared_entities) =
+            split_ensnared_entities_for_spring_split(self, &old_spring, position);
+
+        let first = self.springs.insert(Spring {
+            second_index: new_particle,
+            rest_length: old_spring.rest_length * t,
+            ensnared_entities: new_spring_1_ensnared_entities,
+            ..old_spring
+        });
+        let second = self.springs.insert(Spring {
+            first_index: new_particle,
+            rest_length: old_spring.rest_length * (1.0 - t),
+            ensnared_entities: new_spring_2_ensnared_entities,
+            ..old_spring
+        });
+        self.splits.push(SpringSplit {
+            old: spring_index,
+            t,
+            first,
+            second,
+            joined: true,
+        });
+
+        new_particle
+    }
+}
+
+impl<T> Web<T> {
+    pub fn get_spring(&self, p1: ParticleKey, p2: ParticleKey) -> Option<SpringKey> {
+        for (i, spring) in self.springs.iter() {
+            if spring.first_index == p1 && spring.second_index == p2
+                || spring.first_index == p2 && spring.second_index == p1
+            {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    pub fn get_particle_index(&self, pos: Vec3, ε: f32) -> Option<ParticleKey> {
+        for (i, particle) in self.particles.iter() {
+            if particle.position.distance_squared(pos) < ε * ε {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// kinetic, gravitational and spring energy of the free particles, mostly for debugging
+    pub fn energy(&self) -> f32 {
+        let particles: f32 = self
+            .particles
+            .values()
+            .filter(|particle| !particle.pinned)
+            .map(|particle| {
+                0.5 * particle.mass * particle.velocity.length_squared()
+                    + particle.mass * GRAVITY * particle.position.y
+            })
+            .sum();
+        let springs: f32 = self
+            .springs
+            .values()
+            .map(|spring| {
+                let cur_len = (self.particles[spring.first_index].position
+                    - self.particles[spring.second_index].position)
+                    .length();
+                let stretch = cur_len - spring.rest_length;
+                0.5 * spring.stiffness * stretch * stretch
+            })
+            .sum();
+
+        particles + springs
+    }
+}
+
+/// Advances the web by exactly `h` seconds with its integrator.
+pub fn step<T>(web: &mut Web<T>, air_damping: f32, h: f32) {
+    match web.integrator {
+        Integrator::SemiImplicitEuler => integrator::semi_implicit_euler(web, air_damping, h),
+        Integrator::Verlet => integrator::velocity_verlet(web, air_damping, h),
+        Integrator::Xpbd { iterations } => integrator::xpbd(web, air_damping, h, iterations),
+    }
+
+    for particle in web.particles.values_mut() {
+        if particle.impulse_duration > 0.0 {
+            particle.impulse_duration -= h;
+            if particle.impulse_duration <= 0.0 {
+                particle.impulse = Vec3::ZERO;
+                particle.impulse_duration = 0.0;
+            }
+        }
+    }
+}
+
+/// Recomputes mass and total force of every free particle. Spring forces are left out when
+/// `include_springs` is false, for integrators that solve the springs as constraints instead.
+pub(crate) fn accumulate_forces<T>(
+    web: &mut Web<T>,
+    air_damping: f32,
+    h: f32,
+    include_springs: bool,
+) {
+    for (key, particle) in web.particles.iter_mut() {
+        if particle.pinned {
+            continue;
+        }
+        particle.mass = web.water.get(key).copied().unwrap_or(0.0);
+        particle.force = Vec3::new(0.0, 0.0, 0.0);
+    }
+
+    let Web {
+        particles,
+        springs,
+        mass_per_unit_length,
+        ..
+    } = web;
+
+    for spring in springs.values() {
+        let force = if include_springs {
+            spring.get_force_between(
+                &particles[spring.first_index],
+                &particles[spring.second_index],
+            )
+        } else {
+            Vec3::ZERO
+        };
+        let p1 = spring.first_index;
+        let p2 = spring.second_index;
+
+        // calculate mass of ensnared_entities
+        for ensnared in &spring.ensnared_entities {
+            if !particles[p1].pinned {
+                particles[p1].mass += ensnared.mass * (1.0 - ensnared.snare_position);
+            }
+
+            if !particles[p2].pinned {
+                particles[p2].mass += ensnared.mass * (ensnared.snare_position);
+            }
+        }
+
+        if !particles[p1].pinned {
+            particles[p1].force += force;
+            particles[p1].mass += *mass_per_unit_length * spring.rest_length / 2.0;
+        }
+        if !particles[p2].pinned {
+            particles[p2].force -= force;
+            particles[p2].mass += *mass_per_unit_length * spring.rest_length / 2.0;
+        }
+    }
+
+    for particle in particles.values_mut() {
+        if particle.pinned {
+            continue;
+        }
+
+        particle.force.y -= GRAVITY * particle.mass;
+        particle.force += particle.velocity * -air_damping;
+
+        if particle.impulse_duration > 0.0 {
+            particle.force += particle.impulse * h;
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! Shortest routes along the strands of a web, for walking over silk that is already there.
+
+use super::{ParticleKey, SpringKey, Web};
+use bevy_math::Vec3;
+use slotmap::SecondaryMap;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// A point on a spring, `t` is 0 at its first particle and 1 at its second
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WebLocation {
+    pub spring: SpringKey,
+    pub t: f32,
+}
+
+/// how close to a particle counts as standing on the junction there
+pub const JUNCTION_RADIUS: f32 = 0.03;
+
+/// What holding a direction does to something walking the web
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Steer {
+    /// walk from `from` towards `to_t` on its spring, which is another spring than the one
+    /// steered from when it turned at a junction
+    Walk { from: WebLocation, to_t: f32 },
+    /// no strand goes that way, `junction` is the particle it is standing on if any
+    Stuck { junction: Option<ParticleKey> },
+}
+
+/// One stretch of a route, walked along a single spring
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathLeg {
+    pub spring: SpringKey,
+    pub from_t: f32,
+    pub to_t: f32,
+}
+
+/// particle waiting to be expanded, ordered so the heap pops the lowest estimate first
+struct Open {
+    estimate: f32,
+    particle: ParticleKey,
+}
+
+impl PartialEq for Open {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate.total_cmp(&other.estimate) == Ordering::Equal
+    }
+}
+
+impl Eq for Open {}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl<T> Web<T> {
+    /// Moves the legs of a walk over the web onto the halves of the springs in `splits`. A leg
+    /// across a split point becomes two, a walk across a cut stops at the cut.
+    pub fn follow_splits(&self, legs: &[PathLeg]) -> Vec<PathLeg> {
+        let mut legs = legs.to_vec();
+        for split in &self.splits {
+            let mut followed = Vec::with_capacity(legs.len() + 1);
+            for leg in legs {
+                if leg.spring != split.old {
+                    followed.push(leg);
+                    continue;
+                }
+
+                let (from_spring, from_t) = split.remap(leg.from_t);
+                let (to_spring, to_t) = split.remap(leg.to_t);
+                if from_spring == to_spring {
+                    followed.push(PathLeg {
+                        spring: from_spring,
+                        from_t,
+                        to_t,
+                    });
+                    continue;
+                }
+
+                // the split point is the end of the first half and the start of the second
+                let split_t = if from_spring == split.first { 1.0 } else { 0.0 };
+                followed.push(PathLeg {
+                    spring: from_spring,
+                    from_t,
+                    to_t: split_t,
+                });
+                if !split.joined {
+                    break;
+                }
+                followed.push(PathLeg {
+                    spring: to_spring,
+                    from_t: 1.0 - split_t,
+                    to_t,
+                });
+            }
+            legs = followed;
+        }
+        legs
+    }
+
+    pub fn location_position(&self, location: WebLocation) -> Vec3 {
+        let spring = &self.springs[location.spring];
+        let p1 = self.particles[spring.first_index].position;
+        let p2 = self.particles[spring.second_index].position;
+        p1 + (p2 - p1) * location.t
+    }
+
+    /// Which way to walk from `from`, already heading for `heading_t` on the same spring, to go
+    /// along `direction`. At a junction it turns onto the strand pointing most that way, unless
+    /// it is already walking away from that junction. Otherwise it walks to the end of its own
+    /// strand that lies that way. Strands have to point at least `min_alignment` along it.
+    pub fn steer(
+        &self,
+        from: WebLocation,
+        heading_t: f32,
+        direction: Vec3,
+        min_alignment: f32,
+    ) -> Steer {
+        let spring = &self.springs[from.spring];
+        let position = self.location_position(from);
+        let leaving = |end_t: f32| (heading_t - end_t).abs() > (from.t - end_t).abs();
+        let junction = [(spring.first_index, 0.0), (spring.second_index, 1.0)]
+            .into_iter()
+            .find(|(particle, end_t)| {
+                self.particles[*particle]
+                    .position
+                    .distance_squared(position)
+                    < JUNCTION_RADIUS * JUNCTION_RADIUS
+                    && !leaving(*end_t)
+            })
+            .map(|(particle, _)| particle);
+
+        if let Some(junction) = junction {
+            let junction_position = self.particles[junction].position;
+            let mut best: Option<(SpringKey, f32, f32)> = None;
+            for (key, spring) in self.springs.iter() {
+                let (start_t, other) = if spring.first_index == junction {
+                    (0.0, spring.second_index)
+                } else if spring.second_index == junction {
+                    (1.0, spring.first_index)
+                } else {
+                    continue;
+                };
+
+                let away = (self.particles[other].position - junction_position).normalize_or_zero();
+                let alignment = away.dot(direction);
+                if alignment > min_alignment
+                    && best.is_none_or(|(_, _, best_alignment)| alignment > best_alignment)
+                {
+                    best = Some((key, start_t, alignment));
+                }
+            }
+
+            return match best {
+                // already on it, keep walking from where it is
+                Some((key, start_t, _)) if key == from.spring => Steer::Walk {
+                    from,
+                    to_t: 1.0 - start_t,
+                },
+                Some((key, start_t, _)) => Steer::Walk {
+                    from: WebLocation {
+                        spring: key,
+                        t: start_t,
+                    },
+                    to_t: 1.0 - start_t,
+                },
+                None => Steer::Stuck {
+                    junction: Some(junction),
+                },
+            };
+        }
+
+        let along = (self.particles[spring.second_index].position
+            - self.particles[spring.first_index].position)
+            .normalize_or_zero()
+            .dot(direction);
+        if along > min_alignment {
+            Steer::Walk { from, to_t: 1.0 }
+        } else if along < -min_alignment {
+            Steer::Walk { from, to_t: 0.0 }
+        } else {
+            Steer::Stuck { junction: None }
+        }
+    }
+
+    fn spring_length(&self, spring: SpringKey) -> f32 {
+        let spring = &self.springs[spring];
+        self.particles[spring.first_index]
+            .position
+            .distance(self.particles[spring.second_index].position)
+    }
+
+    /// The point on any spring closest to `point`, if one is within `max_distance`.
+    pub fn closest_location(&self, point: Vec3, max_distance: f32) -> Option<WebLocation> {
+        let mut best: Option<(WebLocation, f32)> = None;
+        for (key, spring) in self.springs.iter() {
+            let p1 = self.particles[spring.first_index].position;
+            let p2 = self.particles[spring.second_index].position;
+            let along = p2 - p1;
+            let length_squared = along.length_squared();
+            let t = if length_squared > 0.0 {
+                ((point - p1).dot(along) / length_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let distance = point.distance(p1 + along * t);
+            if distance <= max_distance && best.is_none_or(|(_, d)| distance < d) {
+                best = Some((WebLocation { spring: key, t }, distance));
+            }
+        }
+        best.map(|(location, _)| location)
+    }
+
+    /// Shortest walk along existing springs between two points on the web, using A* over the
+    /// particles. Returns `None` if the springs don't connect them or either spring is gone.
+    pub fn find_path(&self, from: WebLocation, to: WebLocation) -> Option<Vec<PathLeg>> {
+        if !self.springs.contains_key(from.spring) || !self.springs.contains_key(to.spring) {
+            return None;
+        }
+
+        if from.spring == to.spring {
+            return Some(vec![PathLeg {
+                spring: from.spring,
+                from_t: from.t,
+                to_t: to.t,
+            }]);
+        }
+
+        let mut neighbours: SecondaryMap<ParticleKey, Vec<(SpringKey, ParticleKey)>> =
+            SecondaryMap::new();
+        for (key, spring) in self.springs.iter() {
+            for (a, b) in [
+                (spring.first_index, spring.second_index),
+                (spring.second_index, spring.first_index),
+            ] {
+                if let Some(list) = neighbours.entry(a) {
+                    list.or_default().push((key, b));
+                }
+            }
+        }
+
+        let goal = self.location_position(to);
+        let to_spring = &self.springs[to.spring];
+        let to_length = self.spring_length(to.spring);
+        // walking from either end of the destination spring to the destination
+        let finish = |particle: ParticleKey| -> Option<f32> {
+            if particle == to_spring.first_index {
+                Some(to.t * to_length)
+            } else if particle == to_spring.second_index {
+                Some((1.0 - to.t) * to_length)
+            } else {
+                None
+            }
+        };
+
+        let mut cost: SecondaryMap<ParticleKey, f32> = SecondaryMap::new();
+        let mut came_from: SecondaryMap<ParticleKey, (ParticleKey, SpringKey)> =
+            SecondaryMap::new();
+        let mut open = BinaryHeap::new();
+
+        let from_spring = &self.springs[from.spring];
+        let from_length = self.spring_length(from.spring);
+        for (particle, start_cost) in [
+            (from_spring.first_index, from.t * from_length),
+            (from_spring.second_index, (1.0 - from.t) * from_length),
+        ] {
+            if cost.get(particle).is_none_or(|c| start_cost < *c) {
+                cost.insert(particle, start_cost);
+                open.push(Open {
+                    estimate: start_cost + self.particles[particle].position.distance(goal),
+                    particle,
+                });
+            }
+        }
+
+        // the particle the route leaves the graph at, and the total length
+        let mut best_end: Option<(ParticleKey, f32)> = None;
+
+        while let Some(Open { estimate, particle }) = open.pop() {
+            if let Some((_, best_total)) = best_end {
+                if estimate >= best_total {
+                    break;
+                }
+            }
+            let particle_cost = cost[particle];
+
+            if let Some(rest) = finish(particle) {
+                let total = particle_cost + rest;
+                if best_end.is_none_or(|(_, best_total)| total < best_total) {
+                    best_end = Some((particle, total));
+                }
+            }
+
+            let Some(list) = neighbours.get(particle) else {
+                continue;
+            };
+            for (spring, next) in list {
+                let next_cost = particle_cost + self.spring_length(*spring);
+                if cost.get(*next).is_none_or(|c| next_cost < *c) {
+                    cost.insert(*next, next_cost);
+                    came_from.insert(*next, (particle, *spring));
+                    open.push(Open {
+                        estimate: next_cost + self.particles[*next].position.distance(goal),
+                        particle: *next,
+                    });
+                }
+            }
+        }
+
+        let (end, _) = best_end?;
+
+        // walk back from the end to the start spring
+        let mut particles = vec![end];
+        let mut springs = vec![];
+        let mut current = end;
+        while let Some((previous, spring)) = came_from.get(current) {
+            springs.push(*spring);
+            particles.push(*previous);
+            current = *previous;
+        }
+        particles.reverse();
+        springs.reverse();
+
+        let t_at = |spring: SpringKey, particle: ParticleKey| {
+            if self.springs[spring].first_index == particle {
+                0.0
+            } else {
+                1.0
+            }
+        };
+
+        let mut legs = vec![PathLeg {
+            spring: from.spring,
+            from_t: from.t,
+            to_t: t_at(from.spring, particles[0]),
+        }];
+        for (i, spring) in springs.iter().enumerate() {
+            legs.push(PathLeg {
+                spring: *spring,
+                from_t: t_at(*spring, particles[i]),
+                to_t: t_at(*spring, particles[i + 1]),
+            });
+        }
+        legs.push(PathLeg {
+            spring: to.spring,
+            from_t: t_at(to.spring, end),
+            to_t: to.t,
+        });
+        legs.retain(|leg| leg.from_t != leg.to_t);
+        Some(legs)
+    }
+
+    /// how far the spider walks along the legs, with the springs as they are now
+    pub fn path_length(&self, legs: &[PathLeg]) -> f32 {
+        legs.iter()
+            .map(|leg| (leg.to_t - leg.from_t).abs() * self.spring_length(leg.spring))
+            .sum()
+    }
+}
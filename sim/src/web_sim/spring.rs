@@ -0,0 +1,139 @@
+use super::ensnare::EnsnaredEntity;
+use super::{Particle, ParticleKey, Web};
+use bevy_math::Vec3;
+
+/// How far a spring can be stretched past its rest length, relative to it, before it snaps
+pub const DEFAULT_MAX_STRAIN: f32 = 1.0;
+
+#[derive(Debug, Clone)]
+pub struct Spring<T> {
+    /// handle of first particle
+    pub first_index: ParticleKey,
+    /// handle of second particle
+    pub second_index: ParticleKey,
+    /// stiffness of spring
+    pub stiffness: f32,
+    /// damping on the spring
+    pub damping: f32,
+    /// length of the spring at rest
+    pub rest_length: f32,
+    /// strain at which the spring snaps
+    pub max_strain: f32,
+    /// list of entities that are ensnared
+    pub ensnared_entities: Vec<EnsnaredEntity<T>>,
+}
+
+impl<T> Spring<T> {
+    /// Where the segment `p1`..`p2` crosses this spring when both are seen along `cam_dir`
+    pub fn intersects(&self, web: &Web<T>, cam_dir: Vec3, p1: Vec3, p2: Vec3) -> Option<Vec3> {
+        let sp1 = web.particles[self.first_index].position;
+        let sp2 = web.particles[self.second_index].position;
+
+        let d1 = sp2 - sp1;
+        let d2 = p2 - p1;
+
+        let n1 = cam_dir.cross(d1);
+        let n2 = cam_dir.cross(d2);
+
+        let p1d1 = n1.dot(p1) - n1.dot(sp1) < 0.0;
+        let p1d2 = n1.dot(p2) - n1.dot(sp2) < 0.0;
+        let p2d1 = n2.dot(sp1) - n2.dot(p1) < 0.0;
+        let p2d2 = n2.dot(sp2) - n2.dot(p2) < 0.0;
+
+        if p1d1 == p1d2 || p2d1 == p2d2 {
+            return None;
+        }
+
+        let t2: f32;
+
+        // p1 + t1 * d1 = p2 + t2 * d2
+        // t1 * d1_x = (p2_x - p1_x) + t2 * d2_x
+        // t1 * d1_y = (p2_y - p1_y) + t2 * d2_y
+        // ((p2_y - p1_y) + t2 * d2_y) * d1_x / d1_y = (p2_x - p1_x) + t2 * d2_x
+        // (p2_y - p1_y) * d1_x / d1_y + t2 * d2_y * d1_x / d1_y = (p2_x - p1_x) + t2 * d2_x
+        // (p2_y - p1_y) * d1_x / d1_y - (p2_x - p1_x) = t2 * d2_x - t2 * d2_y * d1_x / d1_y
+        // (p2_y - p1_y) * d1_x / d1_y - (p2_x - p1_x) = t2 * (1 * d2_x - 1 * d2_y * d1_x / d1_y)
+        // t2 = ((p2_y - p1_y) * d1_x / d1_y - (p2_x - p1_x)) / (d2_x - d2_y * d1_x / d1_y)
+        // or
+        // t2 = ((p2_x - p1_x) * d1_y / d1_x - (p2_y - p1_y)) / (d2_y - d2_x * d1_y / d1_x)
+
+        if d1.x == 0.0 {
+            t2 = (sp1.x - p1.x) / d2.x;
+        } else if d1.y == 0.0 {
+            t2 = (sp1.y - p1.y) / d2.y;
+        } else {
+            t2 = ((p1.x - sp1.x) * d1.y / d1.x - (p1.y - sp1.y)) / (d2.y - d2.x * d1.y / d1.x);
+        }
+
+        Some(p1 + d2 * t2)
+    }
+}
+
+impl<T> Spring<T> {
+    pub fn new(
+        web: &Web<T>,
+        first_index: ParticleKey,
+        second_index: ParticleKey,
+        stiffness: f32,
+        damping: f32,
+        ensnared_entities: Vec<EnsnaredEntity<T>>,
+    ) -> Self {
+        Spring {
+            first_index,
+            second_index,
+            stiffness,
+            damping,
+            rest_length: (web.particles[first_index].position
+                - web.particles[second_index].position)
+                .length(),
+            max_strain: DEFAULT_MAX_STRAIN,
+            ensnared_entities,
+        }
+    }
+
+    pub fn new_with_length(
+        _web: &Web<T>,
+        first_index: ParticleKey,
+        second_index: ParticleKey,
+        stiffness: f32,
+        damping: f32,
+        rest_length: f32,
+        ensnared_entities: Vec<EnsnaredEntity<T>>,
+    ) -> Self {
+        Spring {
+            first_index,
+            second_index,
+            stiffness,
+            damping,
+            rest_length,
+            max_strain: DEFAULT_MAX_STRAIN,
+            ensnared_entities,
+        }
+    }
+
+    /// relative extension of the spring, 0 at rest length and negative when compressed
+    pub fn get_strain(&self, web: &Web<T>) -> f32 {
+        let cur_len = (web.particles[self.first_index].position
+            - web.particles[self.second_index].position)
+            .length();
+
+        (cur_len - self.rest_length) / self.rest_length
+    }
+
+    pub fn get_force_p1(&self, web: &Web<T>) -> Vec3 {
+        self.get_force_between(
+            &web.particles[self.first_index],
+            &web.particles[self.second_index],
+        )
+    }
+
+    /// same as `get_force_p1`, for callers that are already borrowing the particles
+    pub fn get_force_between(&self, first: &Particle, second: &Particle) -> Vec3 {
+        let p_diff = first.position - second.position;
+        let cur_len = p_diff.length();
+        let unit = p_diff / cur_len;
+        let v_diff = first.velocity - second.velocity;
+
+        unit * (self.stiffness * (self.rest_length - cur_len) - self.damping * unit.dot(v_diff))
+    }
+}
@@ -0,0 +1,37 @@
+//! Water raindrops leave on the web. It weighs the particles down on top of the silk and
+//! anything caught, and drips off again over time.
+
+use super::{ParticleKey, SpringKey, Web};
+
+/// water lighter than this has all dripped off
+const MIN_WATER: f32 = 1e-4;
+
+impl<T> Web<T> {
+    /// Leaves `mass` of water on a spring at `t`, shared between its two particles by how close
+    /// each is to `t`.
+    pub fn wet(&mut self, spring_index: SpringKey, t: f32, mass: f32) {
+        let Some(spring) = self.springs.get(spring_index) else {
+            return;
+        };
+        let t = t.clamp(0.0, 1.0);
+        for (particle, share) in [(spring.first_index, 1.0 - t), (spring.second_index, t)] {
+            if let Some(water) = self.water.entry(particle) {
+                *water.or_default() += mass.max(0.0) * share;
+            }
+        }
+    }
+
+    /// water sitting on a particle
+    pub fn water_at(&self, particle: ParticleKey) -> f32 {
+        self.water.get(particle).copied().unwrap_or(0.0)
+    }
+
+    /// Lets `drip_rate` of the water on each particle drip off per second.
+    pub fn drip(&mut self, dt: f32, drip_rate: f32) {
+        let kept = (-drip_rate.max(0.0) * dt.max(0.0)).exp();
+        for water in self.water.values_mut() {
+            *water *= kept;
+        }
+        self.water.retain(|_, water| *water > MIN_WATER);
+    }
+}
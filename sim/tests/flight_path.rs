@@ -0,0 +1,172 @@
+use bevy_math::Vec3;
+use ld56_sim::flight_path::{
+    lure_pull, ArcLengthPath, BezierCurve, CatmullRom, FlightPath, Lure, Polyline, ZigZag,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn rng() -> StdRng {
+    StdRng::seed_from_u64(7)
+}
+
+/// a curve that bunches up its `t` near the start, so even steps of `t` are uneven in space
+fn lopsided_curve() -> BezierCurve {
+    BezierCurve::new(
+        Vec3::ZERO,
+        Vec3::ZERO,
+        Vec3::new(0.0, 0.0, 0.1),
+        Vec3::new(0.0, 0.0, 4.0),
+    )
+}
+
+#[test]
+fn bezier_clamps_t_instead_of_panicking() {
+    let curve = lopsided_curve();
+    assert_eq!(curve.at(-1.0), curve.p0);
+    assert_eq!(curve.at(2.0), curve.p3);
+    assert_eq!(curve.at(f32::NAN), curve.p0);
+}
+
+#[test]
+fn random_curves_between_equal_points_dont_panic() {
+    let point = Vec3::new(1.0, 2.0, 3.0);
+    let curve = BezierCurve::random_from_endpoints(point, point, &mut rng());
+    assert_eq!(curve.at(0.5), point);
+
+    let path = ArcLengthPath::new(Polyline::steered(point, point, &mut rng()));
+    assert_eq!(path.length(), 0.0);
+    assert_eq!(path.position(1.0), point);
+    assert_eq!(path.direction(0.0), Vec3::ZERO);
+}
+
+#[test]
+fn empty_paths_stay_at_origin() {
+    assert_eq!(CatmullRom::new(vec![]).at(0.5), Vec3::ZERO);
+    assert_eq!(Polyline::new(vec![]).at(0.5), Vec3::ZERO);
+
+    let path = ArcLengthPath::new(CatmullRom::new(vec![]));
+    assert_eq!(path.length(), 0.0);
+    assert_eq!(path.position(f32::NAN), Vec3::ZERO);
+}
+
+#[test]
+fn catmull_rom_passes_through_its_points() {
+    let points = vec![
+        Vec3::ZERO,
+        Vec3::new(1.0, 1.0, 0.0),
+        Vec3::new(2.0, 0.0, 1.0),
+        Vec3::new(3.0, 2.0, 0.0),
+    ];
+    let curve = CatmullRom::new(points.clone());
+    for (i, point) in points.iter().enumerate() {
+        let t = i as f32 / (points.len() - 1) as f32;
+        assert!(curve.at(t).distance(*point) < 1e-5);
+    }
+}
+
+#[test]
+fn flat_zig_zag_is_a_straight_line() {
+    let path = ArcLengthPath::new(ZigZag::new(Vec3::ZERO, Vec3::new(0.0, 0.0, 5.0), 0.0, 3.0));
+    assert!((path.length() - 5.0).abs() < 1e-4);
+    assert!(path.position(2.5).distance(Vec3::new(0.0, 0.0, 2.5)) < 1e-4);
+}
+
+#[test]
+fn arc_length_moves_at_a_steady_speed() {
+    let curve = lopsided_curve();
+    // in t, the curve barely moves over its first quarter
+    assert!(curve.at(0.25).distance(curve.at(0.0)) < curve.at(1.0).distance(curve.at(0.75)) / 10.0);
+
+    let path = ArcLengthPath::new(curve);
+    let steps = 20;
+    let step = path.length() / steps as f32;
+    for i in 0..steps {
+        let covered = path
+            .position(step * (i + 1) as f32)
+            .distance(path.position(step * i as f32));
+        assert!(
+            (covered - step).abs() < step * 0.05,
+            "step {} covered {}",
+            i,
+            covered
+        );
+    }
+}
+
+#[test]
+fn arc_length_stops_at_the_ends() {
+    let path = ArcLengthPath::new(lopsided_curve());
+    assert_eq!(path.position(-1.0), Vec3::ZERO);
+    assert!(
+        path.position(path.length() + 1.0)
+            .distance(Vec3::new(0.0, 0.0, 4.0))
+            < 1e-5
+    );
+}
+
+#[test]
+fn steered_and_hovering_paths_reach_their_goal() {
+    let start = Vec3::new(-3.0, 0.5, -2.0);
+    let end = Vec3::new(-2.0, 0.2, 3.5);
+
+    let steered = ArcLengthPath::new(Polyline::steered(start, end, &mut rng()));
+    assert_eq!(steered.position(0.0), start);
+    assert_eq!(steered.position(steered.length()), end);
+    assert!(steered.length() >= start.distance(end));
+
+    let centre = start.lerp(end, 0.5);
+    let hovering = ArcLengthPath::new(CatmullRom::hovering(start, centre, 0.3, 2, end));
+    assert!(hovering.position(hovering.length()).distance(end) < 1e-5);
+    // two laps of the circle, a little short of a true circle, on top of getting there
+    assert!(hovering.length() > start.distance(end) + 2.0 * 6.0 * 0.3);
+}
+
+fn lure(x: f32, intensity: f32) -> Lure {
+    Lure {
+        position: Vec3::new(x, 0.0, 0.0),
+        intensity,
+    }
+}
+
+#[test]
+fn lures_out_of_range_dont_pull() {
+    assert_eq!(lure_pull(Vec3::ZERO, &[], 3.0, 0.3), Vec3::ZERO);
+    assert_eq!(
+        lure_pull(Vec3::ZERO, &[lure(5.0, 1.0)], 3.0, 0.3),
+        Vec3::ZERO
+    );
+    assert_eq!(
+        lure_pull(Vec3::ZERO, &[lure(1.0, 0.0)], 3.0, 0.3),
+        Vec3::ZERO
+    );
+}
+
+#[test]
+fn far_lures_pull_towards_themselves() {
+    let pull = lure_pull(Vec3::ZERO, &[lure(2.0, 1.0)], 3.0, 0.3);
+    assert!(pull.x > 0.0);
+    assert!(pull.y.abs() < 1e-6 && pull.z.abs() < 1e-6);
+}
+
+#[test]
+fn brighter_lure_pulls_harder() {
+    let pull = lure_pull(Vec3::ZERO, &[lure(-1.0, 0.5), lure(1.0, 1.2)], 3.0, 0.3);
+    assert!(pull.x > 0.0);
+
+    let dim = lure_pull(Vec3::ZERO, &[lure(1.0, 0.5)], 3.0, 0.3);
+    let bright = lure_pull(Vec3::ZERO, &[lure(1.0, 1.0)], 3.0, 0.3);
+    assert!(bright.length() > dim.length());
+}
+
+#[test]
+fn insects_on_the_orbit_circle_the_lure() {
+    let pull = lure_pull(Vec3::ZERO, &[lure(0.3, 1.0)], 3.0, 0.3);
+    assert!(pull.length() > 0.0);
+    // sideways to the lure and level with it
+    assert!(pull.x.abs() < 1e-5);
+    assert!(pull.y.abs() < 1e-5);
+
+    // too close and it backs off
+    let inside = lure_pull(Vec3::ZERO, &[lure(0.1, 1.0)], 3.0, 0.3);
+    assert!(inside.x < 0.0);
+}
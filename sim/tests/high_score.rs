@@ -0,0 +1,88 @@
+use ld56_sim::high_score::{HighScoreEntry, HighScoreTable, MAX_HIGH_SCORES};
+
+fn entry(survival_time: f32, insects_eaten: u32) -> HighScoreEntry {
+    HighScoreEntry {
+        date: 0,
+        survival_time,
+        insects_eaten,
+        silk_used: 0.0,
+        score: 0,
+    }
+}
+
+fn survival_times(entries: &[&HighScoreEntry]) -> Vec<f32> {
+    entries.iter().map(|entry| entry.survival_time).collect()
+}
+
+fn insects_eaten(entries: &[&HighScoreEntry]) -> Vec<u32> {
+    entries.iter().map(|entry| entry.insects_eaten).collect()
+}
+
+#[test]
+fn rankings_put_the_best_run_first() {
+    let mut table = HighScoreTable::default();
+    table.insert(entry(30.0, 2));
+    table.insert(entry(90.0, 1));
+    table.insert(entry(60.0, 5));
+
+    assert_eq!(
+        survival_times(&table.by_survival_time()),
+        [90.0, 60.0, 30.0]
+    );
+    assert_eq!(insects_eaten(&table.by_insects_eaten()), [5, 2, 1]);
+}
+
+#[test]
+fn runs_outside_both_top_lists_are_dropped() {
+    let mut table = HighScoreTable::default();
+    for i in 0..MAX_HIGH_SCORES + 5 {
+        assert!(table.insert(entry(i as f32, i as u32)));
+    }
+    assert_eq!(table.entries.len(), MAX_HIGH_SCORES);
+    assert_eq!(table.by_survival_time().last().unwrap().survival_time, 5.0);
+
+    assert!(!table.insert(entry(0.5, 0)));
+    assert_eq!(table.entries.len(), MAX_HIGH_SCORES);
+}
+
+#[test]
+fn a_run_that_places_in_one_ranking_is_kept() {
+    let mut table = HighScoreTable::default();
+    for i in 0..MAX_HIGH_SCORES {
+        table.insert(entry(100.0 + i as f32, 1));
+    }
+
+    assert!(table.insert(entry(1.0, 50)));
+    assert_eq!(table.entries.len(), MAX_HIGH_SCORES + 1);
+    assert_eq!(table.by_insects_eaten()[0].insects_eaten, 50);
+    assert_eq!(table.by_survival_time().len(), MAX_HIGH_SCORES);
+    assert!(table
+        .by_survival_time()
+        .iter()
+        .all(|entry| entry.survival_time >= 100.0));
+}
+
+#[test]
+fn ties_are_broken_by_the_other_ranking() {
+    let mut table = HighScoreTable::default();
+    table.insert(entry(60.0, 1));
+    table.insert(entry(60.0, 3));
+    table.insert(entry(20.0, 3));
+
+    assert_eq!(insects_eaten(&table.by_survival_time()), [3, 1, 3]);
+    assert_eq!(
+        survival_times(&table.by_insects_eaten()),
+        [60.0, 20.0, 60.0]
+    );
+}
+
+#[test]
+fn a_full_tie_keeps_the_older_run() {
+    let mut table = HighScoreTable::default();
+    for _ in 0..MAX_HIGH_SCORES {
+        table.insert(entry(10.0, 1));
+    }
+
+    assert!(!table.insert(entry(10.0, 1)));
+    assert_eq!(table.entries.len(), MAX_HIGH_SCORES);
+}
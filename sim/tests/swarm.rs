@@ -0,0 +1,130 @@
+use bevy_math::Vec3;
+use ld56_sim::swarm::{step_boids, Boid, BoidRules, SpatialHash};
+
+const DT: f32 = 1.0 / 60.0;
+
+fn rules() -> BoidRules {
+    BoidRules {
+        separation_radius: 0.05,
+        neighbour_radius: 0.15,
+        separation: 6.0,
+        alignment: 1.0,
+        cohesion: 2.0,
+        goal: 1.5,
+        max_speed: 0.8,
+        max_force: 3.0,
+    }
+}
+
+/// a 5×5×5 block of resting boids `spacing` apart, starting at the origin
+fn block(spacing: f32) -> Vec<Boid> {
+    let mut boids = vec![];
+    for x in 0..5 {
+        for y in 0..5 {
+            for z in 0..5 {
+                boids.push(Boid {
+                    position: Vec3::new(x as f32, y as f32, z as f32) * spacing,
+                    velocity: Vec3::ZERO,
+                });
+            }
+        }
+    }
+    boids
+}
+
+fn centre(boids: &[Boid]) -> Vec3 {
+    boids.iter().map(|boid| boid.position).sum::<Vec3>() / boids.len() as f32
+}
+
+#[test]
+fn spatial_hash_finds_everything_in_range() {
+    let positions: Vec<Vec3> = block(0.1).iter().map(|boid| boid.position).collect();
+    let hash = SpatialHash::new(0.15, &positions);
+    let position = Vec3::ZERO;
+
+    let mut found: Vec<usize> = hash.near(position, 0.15).collect();
+    found.sort();
+    for (i, point) in positions.iter().enumerate() {
+        if point.distance(position) <= 0.15 {
+            assert!(found.binary_search(&i).is_ok(), "missed {}", i);
+        }
+    }
+    // and skips the far corner
+    assert!(found.len() < positions.len());
+    assert!(hash.near(Vec3::splat(10.0), 0.15).next().is_none());
+}
+
+#[test]
+fn spatial_hash_handles_odd_sizes() {
+    let positions = [Vec3::ZERO, Vec3::ONE];
+    assert_eq!(
+        SpatialHash::new(0.0, &positions)
+            .near(Vec3::ZERO, 0.0)
+            .count(),
+        1
+    );
+    assert_eq!(
+        SpatialHash::new(5.0, &positions)
+            .near(Vec3::ZERO, 1.0)
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn swarm_heads_for_its_goal() {
+    let mut boids = block(0.04);
+    let goal = Vec3::new(0.0, 0.0, 3.0);
+    let start = centre(&boids).distance(goal);
+    for _ in 0..120 {
+        step_boids(&mut boids, goal, &rules(), DT);
+    }
+    assert!(centre(&boids).distance(goal) < start - 0.5);
+}
+
+#[test]
+fn swarm_spreads_out_but_stays_together() {
+    let mut boids = block(0.01);
+    let goal = centre(&boids);
+    for _ in 0..300 {
+        step_boids(&mut boids, goal, &rules(), DT);
+    }
+
+    let mut closest = f32::MAX;
+    for (i, a) in boids.iter().enumerate() {
+        for b in &boids[i + 1..] {
+            closest = closest.min(a.position.distance(b.position));
+        }
+    }
+    assert!(closest > 0.01);
+
+    let middle = centre(&boids);
+    assert!(boids
+        .iter()
+        .all(|boid| boid.position.distance(middle) < 1.0));
+}
+
+#[test]
+fn neighbours_line_up() {
+    let mut boids = vec![
+        Boid {
+            position: Vec3::ZERO,
+            velocity: Vec3::new(0.5, 0.0, 0.0),
+        },
+        Boid {
+            position: Vec3::new(0.1, 0.0, 0.0),
+            velocity: Vec3::new(0.0, 0.5, 0.0),
+        },
+    ];
+    let rules = BoidRules {
+        goal: 0.0,
+        cohesion: 0.0,
+        separation: 0.0,
+        ..rules()
+    };
+    let before = boids[0].velocity.angle_between(boids[1].velocity);
+    for _ in 0..30 {
+        step_boids(&mut boids, Vec3::ZERO, &rules, DT);
+    }
+    assert!(boids[0].velocity.angle_between(boids[1].velocity) < before);
+}
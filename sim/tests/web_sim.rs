@@ -0,0 +1,665 @@
+use bevy_math::Vec3;
+use ld56_sim::web_sim::ensnare::EnsnaredEntity;
+use ld56_sim::web_sim::integrator::{Integrator, XPBD_ITERATIONS};
+use ld56_sim::web_sim::path::{PathLeg, Steer, WebLocation, JUNCTION_RADIUS};
+use ld56_sim::web_sim::spring::Spring;
+use ld56_sim::web_sim::{step, Particle, ParticleKey, SpringKey, Web};
+
+const H: f32 = 0.001;
+const AIR_DAMPING: f32 = 0.5;
+
+fn ensnared(entity: u32, snare_position: f32) -> EnsnaredEntity<u32> {
+    EnsnaredEntity {
+        entity,
+        snare_position,
+        mass: 0.1,
+        rotation: 0.0,
+        lerp_rotation: 0.0,
+        done_ensnaring: false,
+    }
+}
+
+/// a free particle hanging below a pinned one, stretched past its rest length
+fn hanging_spring(integrator: Integrator) -> (Web<u32>, ParticleKey, ParticleKey, SpringKey) {
+    let mut web: Web<u32> = Web {
+        integrator,
+        ..Default::default()
+    };
+    let anchor = web.particles.insert(Particle::at(Vec3::ZERO, true));
+    let weight = web
+        .particles
+        .insert(Particle::at(Vec3::new(0.3, -1.5, 0.0), false));
+    let spring = web.springs.insert(Spring::new_with_length(
+        &web,
+        anchor,
+        weight,
+        30.0,
+        0.5,
+        1.0,
+        vec![],
+    ));
+    (web, anchor, weight, spring)
+}
+
+/// a unit square of strands a-b-c-d, with a long way round from a to c through a far particle,
+/// and a strand off on its own
+fn square_web() -> (Web<u32>, [SpringKey; 7]) {
+    let mut web: Web<u32> = Default::default();
+    let [a, b, c, d, far, lone1, lone2] = [
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(1.0, 1.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Vec3::new(0.5, 3.0, 0.0),
+        Vec3::new(5.0, 0.0, 0.0),
+        Vec3::new(6.0, 0.0, 0.0),
+    ]
+    .map(|position| web.particles.insert(Particle::at(position, false)));
+    let springs = [
+        (a, b),
+        (b, c),
+        (c, d),
+        (d, a),
+        (a, far),
+        (far, c),
+        (lone1, lone2),
+    ]
+    .map(|(first, second)| {
+        let spring = Spring::new(&web, first, second, 30.0, 0.5, vec![]);
+        web.springs.insert(spring)
+    });
+    (web, springs)
+}
+
+fn integrators() -> [Integrator; 3] {
+    [
+        Integrator::SemiImplicitEuler,
+        Integrator::Verlet,
+        Integrator::Xpbd {
+            iterations: XPBD_ITERATIONS,
+        },
+    ]
+}
+
+#[test]
+fn energy_decays_with_damping() {
+    for integrator in integrators() {
+        let (mut web, _, weight, _) = hanging_spring(integrator);
+        // the first step gives the particle its mass
+        step(&mut web, AIR_DAMPING, H);
+        let start = web.energy();
+
+        for _ in 0..5000 {
+            step(&mut web, AIR_DAMPING, H);
+        }
+        let middle = web.energy();
+
+        for _ in 0..5000 {
+            step(&mut web, AIR_DAMPING, H);
+        }
+        let end = web.energy();
+
+        assert!(middle < start, "{integrator:?}: {middle} >= {start}");
+        assert!(end <= middle + 1e-4, "{integrator:?}: {end} > {middle}");
+        assert!(web.particles[weight].position.is_finite(), "{integrator:?}");
+    }
+}
+
+#[test]
+fn pinned_particles_do_not_move() {
+    for integrator in integrators() {
+        let (mut web, anchor, _, _) = hanging_spring(integrator);
+        for _ in 0..1000 {
+            step(&mut web, AIR_DAMPING, H);
+        }
+        assert_eq!(web.particles[anchor].position, Vec3::ZERO, "{integrator:?}");
+    }
+}
+
+/// the same pushes and cuts on the same web, stepped the same number of times
+fn run_scripted(integrator: Integrator) -> Vec<[u32; 3]> {
+    let (mut web, [ab, bc, _, da, ..]) = square_web();
+    web.integrator = integrator;
+    for i in 0..2000 {
+        if i % 250 == 0 {
+            web.push_at(ab, 0.3, Vec3::new(0.0, 0.0, 50.0), 0.05);
+            web.push_at(da, 0.7, Vec3::new(10.0, 0.0, -20.0), 0.1);
+        }
+        if i == 1000 {
+            web.cut_spring(bc, 0.4);
+        }
+        step(&mut web, AIR_DAMPING, H);
+    }
+    web.particles
+        .values()
+        .map(|particle| particle.position.to_array().map(f32::to_bits))
+        .collect()
+}
+
+#[test]
+fn same_inputs_give_bit_identical_positions() {
+    for integrator in integrators() {
+        assert_eq!(
+            run_scripted(integrator),
+            run_scripted(integrator),
+            "{integrator:?}"
+        );
+    }
+}
+
+#[test]
+fn split_spring_keeps_the_web_connected() {
+    let (mut web, anchor, weight, spring) = hanging_spring(Integrator::SemiImplicitEuler);
+    web.springs[spring]
+        .ensnared_entities
+        .extend([ensnared(1, 0.25), ensnared(2, 0.75)]);
+    let split_position = web.particles[weight].position * 0.5;
+
+    let middle = web.split_spring(spring, split_position);
+
+    assert!(!web.springs.contains_key(spring));
+    assert_eq!(web.particles.len(), 3);
+    assert_eq!(web.springs.len(), 2);
+    assert_eq!(web.particles[middle].position, split_position);
+    assert!(!web.particles[middle].pinned);
+
+    let first = web.get_spring(anchor, middle).expect("anchor half missing");
+    let second = web.get_spring(middle, weight).expect("weight half missing");
+
+    let first_entities: Vec<u32> = web.springs[first]
+        .ensnared_entities
+        .iter()
+        .map(|e| e.entity)
+        .collect();
+    let second_entities: Vec<u32> = web.springs[second]
+        .ensnared_entities
+        .iter()
+        .map(|e| e.entity)
+        .collect();
+    assert_eq!(first_entities, vec![1]);
+    assert_eq!(second_entities, vec![2]);
+    for spring in web.springs.values() {
+        for ensnared in &spring.ensnared_entities {
+            assert!((ensnared.snare_position - 0.5).abs() < 1e-4);
+        }
+    }
+}
+
+#[test]
+fn split_and_cut_preserve_rest_length() {
+    let (mut web, _, weight, spring) = hanging_spring(Integrator::SemiImplicitEuler);
+    let rest_length = web.springs[spring].rest_length;
+
+    web.split_spring(spring, web.particles[weight].position * 0.25);
+    let total: f32 = web.springs.values().map(|s| s.rest_length).sum();
+    assert!(
+        (total - rest_length).abs() < 1e-5,
+        "{total} != {rest_length}"
+    );
+
+    let key = web.springs.keys().next().unwrap();
+    let before = web.springs[key].rest_length;
+    web.cut_spring(key, 0.3);
+    let total: f32 = web.springs.values().map(|s| s.rest_length).sum();
+    assert!(
+        (total - rest_length).abs() < 1e-5,
+        "{total} != {rest_length}"
+    );
+    assert!(web
+        .springs
+        .values()
+        .any(|s| (s.rest_length - before * 0.3).abs() < 1e-6));
+}
+
+#[test]
+fn split_moves_positions_onto_the_halves() {
+    let (mut web, anchor, weight, spring) = hanging_spring(Integrator::SemiImplicitEuler);
+    let split_position = web.particles[weight].position * 0.25;
+    let walk = PathLeg {
+        spring,
+        from_t: 0.1,
+        to_t: 0.75,
+    };
+    let start = web.location_position(WebLocation { spring, t: 0.1 });
+
+    let middle = web.split_spring(spring, split_position);
+    let first = web.get_spring(anchor, middle).expect("anchor half missing");
+    let second = web.get_spring(middle, weight).expect("weight half missing");
+
+    assert_eq!(web.splits.len(), 1);
+    let (key, t) = web.splits[0].remap(0.1);
+    assert_eq!(key, first);
+    assert!((t - 0.4).abs() < 1e-5, "{t}");
+    assert!(
+        web.location_position(WebLocation { spring: key, t })
+            .distance(start)
+            < 1e-5
+    );
+
+    let legs = web.follow_splits(&[walk]);
+    assert_eq!(legs.len(), 2);
+    assert_eq!(legs[0].spring, first);
+    assert!((legs[0].from_t - 0.4).abs() < 1e-5);
+    assert_eq!(legs[0].to_t, 1.0);
+    assert_eq!(legs[1].spring, second);
+    assert_eq!(legs[1].from_t, 0.0);
+    assert!((legs[1].to_t - 2.0 / 3.0).abs() < 1e-5);
+}
+
+#[test]
+fn walk_across_a_cut_stops_at_the_cut() {
+    let (mut web, anchor, _, spring) = hanging_spring(Integrator::SemiImplicitEuler);
+    let walk = [
+        PathLeg {
+            spring,
+            from_t: 0.75,
+            to_t: 0.25,
+        },
+        PathLeg {
+            spring,
+            from_t: 0.25,
+            to_t: 0.0,
+        },
+    ];
+
+    web.cut_spring(spring, 0.5);
+    let legs = web.follow_splits(&walk);
+
+    assert_eq!(legs.len(), 1);
+    assert_eq!(legs[0].spring, web.splits[0].second);
+    assert!((legs[0].from_t - 0.5).abs() < 1e-5);
+    assert_eq!(legs[0].to_t, 0.0);
+    assert_eq!(web.springs[web.splits[0].first].first_index, anchor);
+}
+
+#[test]
+fn cut_spring_leaves_two_loose_ends_and_returns_ensnared() {
+    let (mut web, anchor, weight, spring) = hanging_spring(Integrator::SemiImplicitEuler);
+    web.springs[spring].ensnared_entities.push(ensnared(7, 0.5));
+
+    let released = web.cut_spring(spring, 0.5);
+
+    assert_eq!(released.len(), 1);
+    assert_eq!(released[0].entity, 7);
+    assert_eq!(web.particles.len(), 4);
+    assert_eq!(web.springs.len(), 2);
+    assert!(web.get_spring(anchor, weight).is_none());
+    assert!(web.springs.values().all(|s| s.ensnared_entities.is_empty()));
+}
+
+#[test]
+fn overstretched_spring_snaps_and_compacts_away() {
+    let (mut web, anchor, weight, spring) = hanging_spring(Integrator::SemiImplicitEuler);
+    web.springs[spring].ensnared_entities.push(ensnared(3, 0.9));
+    web.particles[weight].position = Vec3::new(0.0, -3.0, 0.0);
+
+    let snapped = web.snap_overstretched_springs();
+    assert_eq!(snapped.len(), 1);
+    assert_eq!(snapped[0].released, vec![3]);
+    assert_eq!(snapped[0].position, Vec3::new(0.0, -1.5, 0.0));
+    assert!(web.needs_compact);
+
+    web.compact();
+    assert!(!web.needs_compact);
+    assert_eq!(web.springs.len(), 1);
+    assert_eq!(web.particles.len(), 2);
+    assert!(web.particles.contains_key(anchor));
+    assert!(!web.particles.contains_key(weight));
+}
+
+#[test]
+fn spring_force_pulls_stretched_ends_together() {
+    let (web, _, _, spring) = hanging_spring(Integrator::SemiImplicitEuler);
+    let spring = &web.springs[spring];
+    let force = spring.get_force_p1(&web);
+    let towards_weight =
+        web.particles[spring.second_index].position - web.particles[spring.first_index].position;
+    assert!(force.dot(towards_weight) > 0.0);
+}
+
+#[test]
+fn segments_crossing_intersect() {
+    let mut web: Web<u32> = Default::default();
+    let a = web
+        .particles
+        .insert(Particle::at(Vec3::new(-1.0, 0.0, 0.0), true));
+    let b = web
+        .particles
+        .insert(Particle::at(Vec3::new(1.0, 0.0, 0.0), true));
+    let spring = Spring::new(&web, a, b, 1.0, 0.0, vec![]);
+    let cam_dir = Vec3::new(0.0, 0.0, -1.0);
+
+    let hit = spring
+        .intersects(
+            &web,
+            cam_dir,
+            Vec3::new(0.5, -1.0, 0.0),
+            Vec3::new(0.5, 1.0, 0.0),
+        )
+        .expect("crossing segments should intersect");
+    assert!(hit.distance(Vec3::new(0.5, 0.0, 0.0)) < 1e-5, "{hit}");
+
+    let hit = spring
+        .intersects(
+            &web,
+            cam_dir,
+            Vec3::new(-0.5, -1.0, 0.0),
+            Vec3::new(0.5, 1.0, 0.0),
+        )
+        .expect("diagonal segment should intersect");
+    assert!(hit.distance(Vec3::ZERO) < 1e-5, "{hit}");
+}
+
+#[test]
+fn segments_not_crossing_do_not_intersect() {
+    let mut web: Web<u32> = Default::default();
+    let a = web
+        .particles
+        .insert(Particle::at(Vec3::new(-1.0, 0.0, 0.0), true));
+    let b = web
+        .particles
+        .insert(Particle::at(Vec3::new(1.0, 0.0, 0.0), true));
+    let spring = Spring::new(&web, a, b, 1.0, 0.0, vec![]);
+    let cam_dir = Vec3::new(0.0, 0.0, -1.0);
+
+    // stops short of the spring
+    assert!(spring
+        .intersects(
+            &web,
+            cam_dir,
+            Vec3::new(0.5, 1.0, 0.0),
+            Vec3::new(0.5, 0.1, 0.0)
+        )
+        .is_none());
+    // passes beside its end
+    assert!(spring
+        .intersects(
+            &web,
+            cam_dir,
+            Vec3::new(2.0, -1.0, 0.0),
+            Vec3::new(2.0, 1.0, 0.0)
+        )
+        .is_none());
+    // parallel to it
+    assert!(spring
+        .intersects(
+            &web,
+            cam_dir,
+            Vec3::new(-1.0, 0.5, 0.0),
+            Vec3::new(1.0, 0.5, 0.0)
+        )
+        .is_none());
+}
+
+#[test]
+fn path_on_one_spring_is_a_single_leg() {
+    let (web, [ab, ..]) = square_web();
+
+    let path = web
+        .find_path(
+            WebLocation { spring: ab, t: 0.8 },
+            WebLocation { spring: ab, t: 0.2 },
+        )
+        .expect("no path along one spring");
+
+    assert_eq!(
+        path,
+        vec![PathLeg {
+            spring: ab,
+            from_t: 0.8,
+            to_t: 0.2
+        }]
+    );
+    assert!((web.path_length(&path) - 0.6).abs() < 1e-4);
+}
+
+#[test]
+fn path_takes_the_shortest_way_round() {
+    let (mut web, [ab, bc, cd, da, ..]) = square_web();
+    let from = WebLocation {
+        spring: ab,
+        t: 0.75,
+    };
+    let to = WebLocation {
+        spring: cd,
+        t: 0.25,
+    };
+
+    let path = web.find_path(from, to).expect("no path round the square");
+    assert_eq!(
+        path,
+        vec![
+            PathLeg {
+                spring: ab,
+                from_t: 0.75,
+                to_t: 1.0
+            },
+            PathLeg {
+                spring: bc,
+                from_t: 0.0,
+                to_t: 1.0
+            },
+            PathLeg {
+                spring: cd,
+                from_t: 0.0,
+                to_t: 0.25
+            },
+        ]
+    );
+    assert!((web.path_length(&path) - 1.5).abs() < 1e-4);
+
+    // with b-c gone the way round through a is next shortest
+    web.springs.remove(bc);
+    let path = web.find_path(from, to).expect("no path through a");
+    let springs: Vec<SpringKey> = path.iter().map(|leg| leg.spring).collect();
+    assert_eq!(springs, vec![ab, da, cd]);
+    assert!((web.path_length(&path) - 2.5).abs() < 1e-4);
+}
+
+#[test]
+fn path_to_disconnected_or_removed_spring_is_none() {
+    let (mut web, [ab, .., lone]) = square_web();
+
+    assert!(web
+        .find_path(
+            WebLocation { spring: ab, t: 0.5 },
+            WebLocation {
+                spring: lone,
+                t: 0.5
+            },
+        )
+        .is_none());
+
+    web.springs.remove(lone);
+    assert!(web
+        .find_path(
+            WebLocation { spring: ab, t: 0.5 },
+            WebLocation {
+                spring: lone,
+                t: 0.5
+            },
+        )
+        .is_none());
+}
+
+#[test]
+fn closest_location_finds_nearby_strand() {
+    let (web, [ab, ..]) = square_web();
+
+    let location = web
+        .closest_location(Vec3::new(0.25, -0.02, 0.0), 0.05)
+        .expect("no strand near the click");
+    assert_eq!(location.spring, ab);
+    assert!((location.t - 0.25).abs() < 1e-4);
+    assert!((web.location_position(location) - Vec3::new(0.25, 0.0, 0.0)).length() < 1e-4);
+
+    assert!(web
+        .closest_location(Vec3::new(0.5, 0.5, 0.0), 0.05)
+        .is_none());
+}
+
+/// three strands meeting at a junction at the origin, coming in from the left and leaving to
+/// the right and up
+fn junction_web() -> (Web<u32>, ParticleKey, [SpringKey; 3]) {
+    let mut web: Web<u32> = Default::default();
+    let [junction, left, right, up] = [
+        Vec3::ZERO,
+        Vec3::new(-1.0, 0.0, 0.0),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    ]
+    .map(|position| web.particles.insert(Particle::at(position, true)));
+    let springs = [(left, junction), (junction, right), (up, junction)].map(|(first, second)| {
+        let spring = Spring::new(&web, first, second, 30.0, 0.5, vec![]);
+        web.springs.insert(spring)
+    });
+    (web, junction, springs)
+}
+
+/// holds `direction` for `frames` frames, walking a slow spider's pace each one
+fn walk_holding(
+    web: &Web<u32>,
+    mut at: WebLocation,
+    direction: Vec3,
+    frames: usize,
+) -> WebLocation {
+    let mut heading_t = at.t;
+    for _ in 0..frames {
+        if let Steer::Walk { from, to_t } = web.steer(at, heading_t, direction, 0.3) {
+            at = from;
+            heading_t = to_t;
+        }
+        let step = 0.8 / 60.0 / web.springs[at.spring].rest_length;
+        at.t += (heading_t - at.t).clamp(-step, step);
+    }
+    at
+}
+
+#[test]
+fn steering_turns_at_a_junction_and_walks_off_it() {
+    let (web, junction, [left, right, up]) = junction_web();
+    let at_junction = WebLocation {
+        spring: left,
+        t: 1.0,
+    };
+
+    let walked = walk_holding(&web, at_junction, Vec3::X, 10);
+    assert_eq!(walked.spring, right);
+    assert!(
+        web.location_position(walked)
+            .distance(web.particles[junction].position)
+            > JUNCTION_RADIUS
+    );
+
+    let walked = walk_holding(&web, at_junction, Vec3::Y, 10);
+    assert_eq!(walked.spring, up);
+    assert!(walked.t < 0.9);
+
+    // back the way it came, along its own strand
+    let walked = walk_holding(&web, at_junction, -Vec3::X, 10);
+    assert_eq!(walked.spring, left);
+    assert!(walked.t < 0.9);
+}
+
+#[test]
+fn steering_nowhere_at_a_junction_is_stuck_there() {
+    let (web, junction, [left, ..]) = junction_web();
+    let at_junction = WebLocation {
+        spring: left,
+        t: 1.0,
+    };
+    assert_eq!(
+        web.steer(at_junction, 1.0, -Vec3::Y, 0.3),
+        Steer::Stuck {
+            junction: Some(junction)
+        }
+    );
+}
+
+#[test]
+fn push_at_shares_impulse_by_distance() {
+    let (mut web, [ab, ..]) = square_web();
+    let (a, b) = (web.springs[ab].first_index, web.springs[ab].second_index);
+
+    web.push_at(ab, 0.25, Vec3::new(0.0, 0.0, 100.0), 0.1);
+
+    assert!((web.particles[a].impulse - Vec3::new(0.0, 0.0, 75.0)).length() < 1e-4);
+    assert!((web.particles[b].impulse - Vec3::new(0.0, 0.0, 25.0)).length() < 1e-4);
+    assert_eq!(web.particles[a].impulse_duration, 0.1);
+    assert_eq!(web.particles[b].impulse_duration, 0.1);
+}
+
+#[test]
+fn pushes_on_the_same_particle_add_up() {
+    let (mut web, [ab, ..]) = square_web();
+    let a = web.springs[ab].first_index;
+
+    web.push_at(ab, 0.0, Vec3::new(0.0, 0.0, 100.0), 0.1);
+    web.push_at(ab, 0.0, Vec3::new(0.0, 50.0, 0.0), 0.3);
+    web.push_at(ab, 0.0, Vec3::new(0.0, 0.0, 20.0), 0.2);
+
+    assert!((web.particles[a].impulse - Vec3::new(0.0, 50.0, 120.0)).length() < 1e-4);
+    assert_eq!(web.particles[a].impulse_duration, 0.3);
+}
+
+#[test]
+fn taut_strands_tear_before_slack_ones() {
+    let (mut web, _, _, spring) = hanging_spring(Integrator::SemiImplicitEuler);
+    // rest length 1, stretched to about 1.53
+    let strain = web.springs[spring].get_strain(&web);
+    assert!(strain > 0.5);
+
+    assert!(!web.would_tear(spring, 0.1));
+    assert!(web.would_tear(spring, 1.0 - strain + 0.01));
+
+    web.springs[spring].rest_length = 2.0;
+    assert!(!web.would_tear(spring, 1.0));
+
+    let (mut web, [ab, ..]) = square_web();
+    web.springs.remove(ab);
+    assert!(!web.would_tear(ab, 10.0));
+}
+
+#[test]
+fn wet_shares_water_by_distance() {
+    let (mut web, [ab, ..]) = square_web();
+    let (a, b) = (web.springs[ab].first_index, web.springs[ab].second_index);
+
+    web.wet(ab, 0.25, 0.4);
+    web.wet(ab, 0.25, 0.4);
+
+    assert!((web.water_at(a) - 0.6).abs() < 1e-5);
+    assert!((web.water_at(b) - 0.2).abs() < 1e-5);
+
+    web.springs.remove(ab);
+    web.wet(ab, 0.5, 1.0);
+    assert!((web.water_at(a) - 0.6).abs() < 1e-5);
+}
+
+#[test]
+fn water_drips_off() {
+    let (mut web, [ab, ..]) = square_web();
+    let a = web.springs[ab].first_index;
+    web.wet(ab, 0.0, 1.0);
+
+    web.drip(1.0, 0.5);
+    assert!((web.water_at(a) - (-0.5f32).exp()).abs() < 1e-5);
+
+    web.drip(100.0, 0.5);
+    assert_eq!(web.water_at(a), 0.0);
+    assert!(web.water.is_empty());
+}
+
+#[test]
+fn wet_strands_hang_lower() {
+    let (mut dry, _, dry_weight, _) = hanging_spring(Integrator::SemiImplicitEuler);
+    let (mut wet, _, wet_weight, spring) = hanging_spring(Integrator::SemiImplicitEuler);
+    wet.wet(spring, 1.0, 0.2);
+
+    for _ in 0..5000 {
+        step(&mut dry, AIR_DAMPING, H);
+        step(&mut wet, AIR_DAMPING, H);
+    }
+
+    assert!(wet.particles[wet_weight].position.y < dry.particles[dry_weight].position.y);
+}
@@ -1,6 +1,9 @@
-use bevy::asset::Handle;
-use bevy::prelude::{AnimationGraph, AnimationNodeIndex, Resource};
+use crate::flying_insect::species::InsectSpecies;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, Handle, LoadContext};
+use bevy::prelude::*;
 use bevy_rapier3d::prelude::Group;
+use serde::{Deserialize, Serialize};
 pub const SPAWN_ENEMIES: bool = true;
 
 pub const COLLISION_GROUP_PLAYER: Group = Group::GROUP_1;
@@ -10,9 +13,197 @@ pub const COLLISION_GROUP_ENEMIES: Group = Group::GROUP_4;
 pub const COLLISION_GROUP_PROJECTILES: Group = Group::GROUP_5;
 pub const COLLISION_GROUP_ALL: Group = Group::ALL;
 
+/// Path of the tuning file, relative to the assets folder.
+pub const GAME_CONFIG_PATH: &str = "game.config.ron";
+
 pub fn 冰淇淋() -> bool {
     rand::random()
 }
 pub fn 熊猫() -> f32 {
     rand::random()
 }
+
+pub struct GameConfigPlugin;
+
+/// Gameplay tuning, read from `assets/game.config.ron`. Until the file is loaded, or if it is
+/// missing or broken, the defaults below are used. Missing fields also fall back to them.
+#[derive(Asset, Resource, TypePath, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    /// food the spider starts with and can hold at most
+    pub max_food: f32,
+    /// silk the spider starts with and can hold at most
+    pub max_silk: f32,
+    /// food the spider loses per second
+    pub food_drain: f32,
+    /// silk the spider makes per second
+    pub silk_regen: f32,
+    /// silk the spider gets from eating an insect
+    pub feast_silk: f32,
+    /// stiffness of the strands the spider spins
+    pub silk_stiffness: f32,
+    /// damping of the strands the spider spins
+    pub silk_damping: f32,
+    /// how thick the silk is drawn and collides
+    pub silk_thickness: f32,
+    /// how fast the spider turns, in half turns per second
+    pub spider_rotate_speed: f32,
+    /// points for eating an insect, before the combo multiplier
+    pub feast_points: f32,
+    /// points for catching an insect in the web
+    pub ensnare_points: f32,
+    /// points for every second survived
+    pub survival_points_per_second: f32,
+    /// seconds after eating an insect in which the next one counts towards the combo
+    pub combo_window: f32,
+    /// how much each insect in a combo adds to the multiplier
+    pub combo_step: f32,
+    /// highest multiplier a combo can reach
+    pub max_combo_multiplier: f32,
+    /// length of one web simulation substep in seconds, the web steps on `FixedUpdate`
+    pub web_substep: f32,
+    /// most substeps the web runs in one frame to catch up, the web drops the time beyond that
+    pub web_max_substeps_per_frame: u32,
+    /// the kinds of insect that fly in, and how often each comes
+    pub insect_species: Vec<InsectSpecies>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            max_food: 10.0,
+            max_silk: 6.0,
+            food_drain: 0.25,
+            silk_regen: 0.2,
+            feast_silk: 1.5,
+            silk_stiffness: 20.0,
+            silk_damping: 0.5,
+            silk_thickness: 0.03,
+            spider_rotate_speed: 5.6,
+            feast_points: 100.0,
+            ensnare_points: 10.0,
+            survival_points_per_second: 1.0,
+            combo_window: 6.0,
+            combo_step: 0.5,
+            max_combo_multiplier: 4.0,
+            web_substep: 0.001,
+            web_max_substeps_per_frame: 100,
+            insect_species: InsectSpecies::defaults(),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct GameConfigHandle(Handle<GameConfig>);
+
+#[derive(Default)]
+struct GameConfigLoader;
+
+#[derive(Debug)]
+pub enum GameConfigLoaderError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for GameConfigLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameConfigLoaderError::Io(err) => write!(f, "{}", err),
+            GameConfigLoaderError::Parse(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for GameConfigLoaderError {}
+
+impl AssetLoader for GameConfigLoader {
+    type Asset = GameConfig;
+    type Settings = ();
+    type Error = GameConfigLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<GameConfig, GameConfigLoaderError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(GameConfigLoaderError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(GameConfigLoaderError::Parse)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron"]
+    }
+}
+
+impl Plugin for GameConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<GameConfig>();
+        app.init_asset_loader::<GameConfigLoader>();
+        app.init_resource::<GameConfig>();
+        app.add_systems(Startup, load_game_config);
+        app.add_systems(PreUpdate, apply_game_config);
+
+        #[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
+        app.add_systems(Update, watch_game_config);
+    }
+}
+
+fn load_game_config(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GameConfigHandle(asset_server.load(GAME_CONFIG_PATH)));
+}
+
+/// copies the config asset into the `GameConfig` resource every time it (re)loads
+fn apply_game_config(
+    mut ev_asset: EventReader<AssetEvent<GameConfig>>,
+    configs: Res<Assets<GameConfig>>,
+    handle: Option<Res<GameConfigHandle>>,
+    mut config: ResMut<GameConfig>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+
+    for event in ev_asset.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            if *id != handle.0.id() {
+                continue;
+            }
+            if let Some(loaded) = configs.get(*id) {
+                debug!("Loaded game config: {:?}", loaded);
+                *config = loaded.clone();
+            }
+        }
+    }
+}
+
+/// Reloads the config when the file changes, so it can be tuned while the game is running.
+/// Only in debug builds on desktop, release builds read it once.
+#[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
+fn watch_game_config(
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    mut last_modified: Local<Option<std::time::SystemTime>>,
+    mut timer: Local<Timer>,
+) {
+    if timer.duration().is_zero() {
+        *timer = Timer::from_seconds(1.0, TimerMode::Repeating);
+    }
+    if !timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let path = std::path::Path::new("assets").join(GAME_CONFIG_PATH);
+    let Ok(modified) = std::fs::metadata(path).and_then(|metadata| metadata.modified()) else {
+        return;
+    };
+
+    if last_modified.is_some_and(|last| last != modified) {
+        asset_server.reload(GAME_CONFIG_PATH);
+    }
+    *last_modified = Some(modified);
+}
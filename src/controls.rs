@@ -0,0 +1,407 @@
+use crate::storage::{read_text, write_text, StorageError};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever the layout of [`ControlBindings`] changes.
+pub const CONTROLS_VERSION: u32 = 1;
+
+/// File name in the platform data dir on desktop, key in `localStorage` on the web.
+pub const CONTROLS_FILE: &str = "controls.ron";
+
+pub struct ControlsPlugin;
+
+/// Something the player can do, independent of the device doing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ControlAction {
+    /// walk or spin silk towards the cursor
+    MoveTo,
+    /// cut the web under the cursor
+    Cut,
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    /// spin silk in the direction the spider is aiming
+    LaySilk,
+    /// cut the web just ahead of the spider
+    CutAhead,
+    Pause,
+    /// start over from the pause or game over screen
+    Restart,
+    /// open the controls menu from the title or pause screen
+    ShowControls,
+    /// open or close the high scores from the title screen
+    ShowHighScores,
+    /// step through the difficulties on the title screen
+    ChangeDifficulty,
+    /// show the physics colliders
+    ToggleDebug,
+    SwapCamera,
+    /// switch the web to the next integrator
+    CycleIntegrator,
+    SaveWeb,
+    LoadWeb,
+}
+
+impl ControlAction {
+    /// every action, in the order the controls menu lists them
+    pub const ALL: [ControlAction; 18] = [
+        ControlAction::MoveTo,
+        ControlAction::Cut,
+        ControlAction::MoveUp,
+        ControlAction::MoveDown,
+        ControlAction::MoveLeft,
+        ControlAction::MoveRight,
+        ControlAction::LaySilk,
+        ControlAction::CutAhead,
+        ControlAction::Pause,
+        ControlAction::Restart,
+        ControlAction::ShowControls,
+        ControlAction::ShowHighScores,
+        ControlAction::ChangeDifficulty,
+        ControlAction::ToggleDebug,
+        ControlAction::SwapCamera,
+        ControlAction::CycleIntegrator,
+        ControlAction::SaveWeb,
+        ControlAction::LoadWeb,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ControlAction::MoveTo => "Move to cursor",
+            ControlAction::Cut => "Cut at cursor",
+            ControlAction::MoveUp => "Move up",
+            ControlAction::MoveDown => "Move down",
+            ControlAction::MoveLeft => "Move left",
+            ControlAction::MoveRight => "Move right",
+            ControlAction::LaySilk => "Spin silk ahead",
+            ControlAction::CutAhead => "Cut ahead",
+            ControlAction::Pause => "Pause",
+            ControlAction::Restart => "Restart",
+            ControlAction::ShowControls => "Controls",
+            ControlAction::ShowHighScores => "High scores",
+            ControlAction::ChangeDifficulty => "Change difficulty",
+            ControlAction::ToggleDebug => "Toggle debug view",
+            ControlAction::SwapCamera => "Swap camera",
+            ControlAction::CycleIntegrator => "Cycle web integrator",
+            ControlAction::SaveWeb => "Save web",
+            ControlAction::LoadWeb => "Load web",
+        }
+    }
+}
+
+/// A button on any device that can trigger an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ControlBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// the button on every connected gamepad
+    Gamepad(GamepadButtonType),
+}
+
+impl ControlBinding {
+    pub fn is_gamepad(&self) -> bool {
+        matches!(self, ControlBinding::Gamepad(_))
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            ControlBinding::Key(key) => {
+                let name = format!("{:?}", key);
+                match name.strip_prefix("Key").or(name.strip_prefix("Digit")) {
+                    Some(short) => short.to_string(),
+                    None => name,
+                }
+            }
+            ControlBinding::Mouse(MouseButton::Left) => "Left click".to_string(),
+            ControlBinding::Mouse(MouseButton::Right) => "Right click".to_string(),
+            ControlBinding::Mouse(MouseButton::Middle) => "Middle click".to_string(),
+            ControlBinding::Mouse(button) => format!("Mouse {:?}", button),
+            ControlBinding::Gamepad(button) => format!("Pad {:?}", button),
+        }
+    }
+}
+
+/// Which buttons trigger which action. Change it at runtime to remap the controls, the controls
+/// menu saves it to [`CONTROLS_FILE`].
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlBindings {
+    pub version: u32,
+    pub bindings: HashMap<ControlAction, Vec<ControlBinding>>,
+    /// whether the left stick moves the spider as well as the move actions
+    pub use_left_stick: bool,
+    /// stick deflection below this is ignored
+    pub stick_dead_zone: f32,
+}
+
+impl Default for ControlBindings {
+    fn default() -> Self {
+        use ControlAction::*;
+        use ControlBinding::*;
+
+        let bindings = [
+            (MoveTo, vec![Mouse(MouseButton::Left)]),
+            (Cut, vec![Mouse(MouseButton::Right)]),
+            (
+                MoveUp,
+                vec![
+                    Key(KeyCode::KeyW),
+                    Key(KeyCode::ArrowUp),
+                    Gamepad(GamepadButtonType::DPadUp),
+                ],
+            ),
+            (
+                MoveDown,
+                vec![
+                    Key(KeyCode::KeyS),
+                    Key(KeyCode::ArrowDown),
+                    Gamepad(GamepadButtonType::DPadDown),
+                ],
+            ),
+            (
+                MoveLeft,
+                vec![
+                    Key(KeyCode::KeyA),
+                    Key(KeyCode::ArrowLeft),
+                    Gamepad(GamepadButtonType::DPadLeft),
+                ],
+            ),
+            (
+                MoveRight,
+                vec![
+                    Key(KeyCode::KeyD),
+                    Key(KeyCode::ArrowRight),
+                    Gamepad(GamepadButtonType::DPadRight),
+                ],
+            ),
+            (
+                LaySilk,
+                vec![Key(KeyCode::Space), Gamepad(GamepadButtonType::South)],
+            ),
+            (
+                CutAhead,
+                vec![
+                    Key(KeyCode::KeyX),
+                    Gamepad(GamepadButtonType::West),
+                    Gamepad(GamepadButtonType::RightTrigger),
+                ],
+            ),
+            (
+                Pause,
+                vec![
+                    Key(KeyCode::Escape),
+                    Key(KeyCode::KeyP),
+                    Gamepad(GamepadButtonType::Start),
+                ],
+            ),
+            (
+                Restart,
+                vec![Key(KeyCode::KeyR), Gamepad(GamepadButtonType::Select)],
+            ),
+            (ShowControls, vec![Key(KeyCode::KeyC)]),
+            (ShowHighScores, vec![Key(KeyCode::KeyH)]),
+            (ChangeDifficulty, vec![Key(KeyCode::Tab)]),
+            (ToggleDebug, vec![Key(KeyCode::F12)]),
+            (SwapCamera, vec![Key(KeyCode::KeyQ)]),
+            (CycleIntegrator, vec![Key(KeyCode::F11)]),
+            (SaveWeb, vec![Key(KeyCode::F5)]),
+            (LoadWeb, vec![Key(KeyCode::F9)]),
+        ];
+
+        ControlBindings {
+            version: CONTROLS_VERSION,
+            bindings: bindings.into_iter().collect(),
+            use_left_stick: true,
+            stick_dead_zone: 0.2,
+        }
+    }
+}
+
+impl ControlBindings {
+    /// Binds the button to the action instead of the action's other buttons on the same kind of
+    /// device, so picking a key keeps the gamepad binding. The button is taken away from any
+    /// other action so one press never does two things.
+    pub fn rebind(&mut self, action: ControlAction, binding: ControlBinding) {
+        for bindings in self.bindings.values_mut() {
+            bindings.retain(|b| *b != binding);
+        }
+        let bindings = self.bindings.entry(action).or_default();
+        bindings.retain(|b| b.is_gamepad() != binding.is_gamepad());
+        bindings.push(binding);
+    }
+
+    /// all buttons bound to the action, for showing in menus
+    pub fn label(&self, action: ControlAction) -> String {
+        let labels: Vec<String> = self
+            .bindings
+            .get(&action)
+            .map(|bindings| bindings.iter().map(|b| b.label()).collect())
+            .unwrap_or_default();
+        if labels.is_empty() {
+            "unbound".to_string()
+        } else {
+            labels.join(", ")
+        }
+    }
+
+    pub fn to_ron(&self) -> Result<String, ControlsError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(ControlsError::Serialize)
+    }
+
+    /// Reads saved bindings. Actions added since the file was written get their default
+    /// bindings.
+    pub fn from_ron(text: &str) -> Result<ControlBindings, ControlsError> {
+        let mut bindings: ControlBindings = ron::from_str(text).map_err(ControlsError::Parse)?;
+        if bindings.version > CONTROLS_VERSION {
+            return Err(ControlsError::UnsupportedVersion(bindings.version));
+        }
+
+        let defaults = ControlBindings::default();
+        for action in ControlAction::ALL {
+            if !bindings.bindings.contains_key(&action) {
+                let default_bindings = defaults.bindings[&action].clone();
+                bindings.bindings.insert(action, default_bindings);
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+#[derive(Debug)]
+pub enum ControlsError {
+    Storage(StorageError),
+    Serialize(ron::Error),
+    Parse(ron::error::SpannedError),
+    UnsupportedVersion(u32),
+}
+
+impl std::fmt::Display for ControlsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControlsError::Storage(err) => write!(f, "{}", err),
+            ControlsError::Serialize(err) => write!(f, "{}", err),
+            ControlsError::Parse(err) => write!(f, "{}", err),
+            ControlsError::UnsupportedVersion(version) => write!(
+                f,
+                "controls version {} is newer than {}",
+                version, CONTROLS_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ControlsError {}
+
+pub fn load_control_bindings() -> Result<ControlBindings, ControlsError> {
+    match read_text(CONTROLS_FILE).map_err(ControlsError::Storage)? {
+        Some(text) => ControlBindings::from_ron(&text),
+        None => Ok(Default::default()),
+    }
+}
+
+pub fn save_control_bindings(bindings: &ControlBindings) -> Result<(), ControlsError> {
+    write_text(CONTROLS_FILE, &bindings.to_ron()?).map_err(ControlsError::Storage)
+}
+
+/// The actions held and pressed this frame, filled from [`ControlBindings`] before `Update`
+#[derive(Resource, Default, Debug)]
+pub struct ControlState {
+    pressed: HashSet<ControlAction>,
+    just_pressed: HashSet<ControlAction>,
+    /// combined move actions and stick, x is right and y is up, at most length 1
+    pub movement: Vec2,
+}
+
+impl ControlState {
+    pub fn pressed(&self, action: ControlAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: ControlAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+}
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(match load_control_bindings() {
+            Ok(bindings) => bindings,
+            Err(err) => {
+                error!("Could not load controls: {}", err);
+                Default::default()
+            }
+        });
+        app.init_resource::<ControlState>();
+        app.add_systems(PreUpdate, read_controls.after(InputSystem));
+    }
+}
+
+fn read_controls(
+    bindings: Res<ControlBindings>,
+    mut state: ResMut<ControlState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+) {
+    let pressed = |binding: &ControlBinding| match binding {
+        ControlBinding::Key(key) => keys.pressed(*key),
+        ControlBinding::Mouse(button) => mouse_buttons.pressed(*button),
+        ControlBinding::Gamepad(button_type) => gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.pressed(GamepadButton::new(gamepad, *button_type))),
+    };
+    let just_pressed = |binding: &ControlBinding| match binding {
+        ControlBinding::Key(key) => keys.just_pressed(*key),
+        ControlBinding::Mouse(button) => mouse_buttons.just_pressed(*button),
+        ControlBinding::Gamepad(button_type) => gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, *button_type))),
+    };
+
+    state.pressed.clear();
+    state.just_pressed.clear();
+    for (action, action_bindings) in &bindings.bindings {
+        if action_bindings.iter().any(pressed) {
+            state.pressed.insert(*action);
+        }
+        if action_bindings.iter().any(just_pressed) {
+            state.just_pressed.insert(*action);
+        }
+    }
+
+    let mut movement = Vec2::ZERO;
+    for (action, direction) in [
+        (ControlAction::MoveUp, Vec2::Y),
+        (ControlAction::MoveDown, Vec2::NEG_Y),
+        (ControlAction::MoveLeft, Vec2::NEG_X),
+        (ControlAction::MoveRight, Vec2::X),
+    ] {
+        if state.pressed(action) {
+            movement += direction;
+        }
+    }
+
+    if bindings.use_left_stick {
+        for gamepad in gamepads.iter() {
+            let stick = Vec2::new(
+                gamepad_axes
+                    .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                    .unwrap_or(0.0),
+                gamepad_axes
+                    .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                    .unwrap_or(0.0),
+            );
+            if stick.length() > bindings.stick_dead_zone {
+                movement += stick;
+            }
+        }
+    }
+
+    state.movement = movement.clamp_length_max(1.0);
+}
@@ -1,11 +1,13 @@
-use super::fruit_fly::DAVID_DEBUG;
-use crate::flying_insect::fruit_fly::{fly_hentai_anime_setup, spawn_fruit_fly, Animation};
-use crate::flying_obstacle::flying_obstacle::{ROCK_TIMER_MULTIPLIER, ROCK_TIMER_START};
-use crate::game::GameState;
+use super::spawn::DAVID_DEBUG;
+use crate::flying_insect::spawn::{fly_hentai_anime_setup, spawn_insect, InsectAnimations};
+use crate::flying_insect::species::InsectSpecies;
+use crate::flying_insect::swarm::{clear_swarms, move_swarms, SwarmMember};
+use crate::game::{GameRestarted, GameState};
 use crate::mesh_loader::{self, load_level, MeshLoader};
+use crate::pumpkin::{Pumpkin, PUMPKIN_LIGHT_INTENSITY};
 use crate::spider::Spider;
 use crate::web::ensnare::{free_enemy_from_web, Ensnared};
-use crate::web::Web;
+use crate::web::WebSimulation;
 use bevy::app::{App, Plugin, Startup, Update};
 use bevy::asset::{AssetServer, Assets, Handle};
 use bevy::color::Color;
@@ -13,21 +15,12 @@ use bevy::math::{Mat3, Vec3};
 use bevy::pbr::StandardMaterial;
 use bevy::{log, prelude::*};
 use bevy_health_bar3d::prelude::Percentage;
+use ld56_sim::flight_path::{lure_pull, ArcLengthPath, Lure};
 use rand::Rng;
 use std::f32::consts::PI;
 use std::time::Duration;
 
 pub struct FlyingInsectPlugin;
-pub const FLY_TIMER_START: f32 = 1500.0;
-pub const FLY_TIMER_MULT: f32 = 500.0;
-pub(crate) fn fly_timer_value(t: f32) -> f32 {
-    (FLY_TIMER_START + (ROCK_TIMER_MULTIPLIER * t.sqrt())) / 1000.0
-}
-
-#[derive(Resource)]
-pub struct FruitFlySpawnTimer {
-    pub timer: Timer,
-}
 
 #[derive(Resource)]
 pub struct EnsnareRollModel {
@@ -40,7 +33,10 @@ impl Plugin for FlyingInsectPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, load_ensnare_roll_model.after(mesh_loader::setup));
         app.add_systems(Update, move_flying_insect.run_if(in_state(GameState::Game)));
-        app.add_systems(Update, spawn_fruit_fly.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, spawn_insect.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, move_swarms.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, clear_insects);
+        app.add_systems(Update, clear_swarms);
         app.add_systems(
             Update,
             insect_ensnared_tick_cooking_and_free.run_if(in_state(GameState::Game)),
@@ -53,97 +49,55 @@ impl Plugin for FlyingInsectPlugin {
             Update,
             fly_hentai_anime_setup.run_if(in_state(GameState::Game)),
         );
-        app.insert_resource(FruitFlySpawnTimer {
-            timer: Timer::new(
-                Duration::from_secs_f32(fly_timer_value(0.0)),
-                TimerMode::Repeating,
-            ),
-        });
 
         app.insert_resource(EnsnareRollModel {
             mesh: Default::default(),
             material: Default::default(),
             transform: Default::default(),
         });
-        app.insert_resource(Animation {
-            animation_list: vec![],
-            graph: Default::default(),
-        });
+        app.init_resource::<InsectAnimations>();
     }
 }
 
-#[derive(Reflect)]
-pub struct BezierCurve {
-    p0: Vec3,
-    p1: Vec3,
-    p2: Vec3,
-    p3: Vec3,
-}
-
-impl BezierCurve {
-    pub fn new(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3) -> Self {
-        BezierCurve { p0, p1, p2, p3 }
+/// despawns every insect and its silk roll
+fn clear_insects(
+    mut commands: Commands,
+    mut ev_restarted: EventReader<GameRestarted>,
+    insect_query: Query<(Entity, &FlyingInsect)>,
+) {
+    if ev_restarted.read().count() == 0 {
+        return;
     }
 
-    pub fn at(&self, t: f32) -> Vec3 {
-        if t < 0.0 || t > 1.0 {
-            panic!("CRINGE INVALID t USED FOR FLY BEZIER CURVE");
+    for (entity, insect) in &insect_query {
+        if let Some(rolled_ensnare_entity) = insect.rolled_ensnare_entity {
+            commands.entity(rolled_ensnare_entity).despawn_recursive();
         }
-
-        f32::powf(1.0 - t, 3.0) * self.p0
-            + 3.0 * (1.0 - t) * (1.0 - t) * t * self.p1
-            + 3.0 * (1.0 - t) * t * t * self.p2
-            + t * t * t * self.p3
-    }
-
-    pub fn random_from_endpoints(p0: Vec3, p3: Vec3) -> Self {
-        let (p1, p2) = generate_bezier_handles(p0, p3);
-        BezierCurve::new(p0, p1, p2, p3)
-    }
-
-    pub fn tangent_at(&self, t: f32) -> Vec3 {
-        3.0 * f32::powf(1.0 - t, 2.0) * (self.p1 - self.p0)
-            + 6.0 * (1.0 - t) * t * (self.p2 - self.p1)
-            + 3.0 * t * t * (self.p3 - self.p2)
-    }
-}
-
-fn generate_bezier_handles(p0: Vec3, p3: Vec3) -> (Vec3, Vec3) {
-    let mut rng = rand::thread_rng();
-
-    let x_min = p0.x.min(p3.x);
-    let y_min = p0.y.min(p3.y);
-    let z_min = p0.z.min(p3.z);
-    let x_max = p0.x.max(p3.x);
-    let y_max = p0.y.max(p3.y);
-    let z_max = p0.z.max(p3.z);
-
-    let p1 = Vec3::new(
-        rng.gen_range(x_min..x_max),
-        rng.gen_range(y_min..y_max),
-        rng.gen_range(z_min..z_max),
-    );
-
-    let p2 = Vec3::new(
-        rng.gen_range(x_min..x_max),
-        rng.gen_range(y_min..y_max),
-        rng.gen_range(z_min..z_max),
-    );
-
-    if p2.z < p1.z {
-        (p2, p1)
-    } else {
-        (p1, p2)
+        commands.entity(entity).despawn_recursive();
     }
 }
 
-#[derive(Component, Reflect)]
+#[derive(Component)]
 pub struct FlyingInsect {
+    /// name of its `InsectSpecies`
+    pub species: String,
+    /// world units it flies per second
     pub speed: f32,
-    pub progress: f32,
+    /// how far along its path it has flown
+    pub distance: f32,
     pub weight: f32,
+    pub struggle_strength: f32,
+    /// food the spider gets from eating it
+    pub food: f32,
     pub offset: f32,
-    pub path: BezierCurve,
+    pub path: ArcLengthPath,
+    /// how strongly pumpkin lights draw it off its path
+    pub light_attraction: f32,
+    /// how far lights have drawn it away from its path
+    pub lure_offset: Vec3,
+    pub lure_velocity: Vec3,
+    /// seconds it has spent circling lights
+    pub lured_time: f32,
     pub break_free_position: Vec3,
     pub snare_roll_progress: f32,
     pub snare_timer: Timer,
@@ -154,19 +108,29 @@ pub struct FlyingInsect {
 }
 
 impl FlyingInsect {
-    pub fn new(speed: f32, weight: f32, bezier: BezierCurve) -> Self {
+    pub fn new(species: &InsectSpecies, path: ArcLengthPath) -> Self {
         let mut rng = rand::thread_rng();
         let mut new_flying = FlyingInsect {
-            speed,
-            progress: 0.0,
-            weight,
+            species: species.name.clone(),
+            speed: species.speed,
+            distance: 0.0,
+            weight: species.weight,
+            struggle_strength: species.struggle_strength,
+            food: species.food,
             offset: rng.gen_range(0.0..2.0 * PI),
-            path: bezier,
+            path,
+            light_attraction: species.light_attraction,
+            lure_offset: Vec3::ZERO,
+            lure_velocity: Vec3::ZERO,
+            lured_time: 0.0,
             break_free_position: Vec3::new(0.0, 0.0, 0.0),
             snare_roll_progress: 0.0,
             cooking_progress: 0.0,
             snare_timer: Timer::new(Duration::from_secs(2), TimerMode::Repeating),
-            cooking_timer: Timer::new(Duration::from_secs(5), TimerMode::Repeating),
+            cooking_timer: Timer::new(
+                Duration::from_secs_f32(species.cook_time.max(0.0)),
+                TimerMode::Repeating,
+            ),
             freed_timer: Timer::new(Duration::from_secs(15), TimerMode::Repeating),
             rolled_ensnare_entity: None,
         };
@@ -178,18 +142,63 @@ impl FlyingInsect {
     }
 }
 
+/// lights further away than this don't draw insects
+const LURE_RANGE: f32 = 3.0;
+/// how far from a light lured insects circle it
+const LURE_ORBIT_RADIUS: f32 = 0.35;
+/// weaker pulls than this let an insect go back to its path
+const MIN_LURE_PULL: f32 = 0.15;
+/// seconds an insect circles lights before it loses interest and flies on
+const MAX_LURE_TIME: f32 = 10.0;
+/// how quickly a lured insect turns, higher is sharper
+const LURE_TURN_RATE: f32 = 2.5;
+
+/// Moves insects along their paths. Insects drawn to light leave their path to circle the
+/// pumpkins, more so while they flicker bright, and stop following it until they lose interest.
 fn move_flying_insect(
-    mut fly_query: Query<(&mut FlyingInsect, &mut Transform, Entity), Without<Ensnared>>,
+    mut fly_query: Query<
+        (&mut FlyingInsect, &mut Transform, Entity),
+        (Without<Ensnared>, Without<SwarmMember>),
+    >,
+    pumpkin_query: Query<(&GlobalTransform, &PointLight), With<Pumpkin>>,
     time: Res<Time>,
     mut commands: Commands,
 ) {
+    let lures: Vec<Lure> = pumpkin_query
+        .iter()
+        .map(|(transform, light)| Lure {
+            position: transform.translation(),
+            intensity: light.intensity / PUMPKIN_LIGHT_INTENSITY,
+        })
+        .collect();
+    let dt = time.delta_seconds();
+
     for (mut fly, mut transform, entity) in &mut fly_query {
-        fly.progress += time.delta_seconds() * fly.speed;
+        let position = fly.path.position(fly.distance) + fly.lure_offset;
+        let pull = if fly.light_attraction > 0.0 && fly.lured_time < MAX_LURE_TIME {
+            lure_pull(position, &lures, LURE_RANGE, LURE_ORBIT_RADIUS) * fly.light_attraction
+        } else {
+            Vec3::ZERO
+        };
+
+        let wanted_velocity = if pull.length() >= MIN_LURE_PULL {
+            fly.lured_time += dt;
+            pull.normalize() * fly.speed
+        } else {
+            fly.distance += dt * fly.speed;
+            // drift back onto the path
+            (-fly.lure_offset * LURE_TURN_RATE).clamp_length_max(fly.speed)
+        };
+        let turn = (dt * LURE_TURN_RATE).min(1.0);
+        fly.lure_velocity = fly.lure_velocity.lerp(wanted_velocity, turn);
+        let lure_velocity = fly.lure_velocity;
+        fly.lure_offset += lure_velocity * dt;
 
-        if fly.progress > 1.0 {
+        if fly.distance > fly.path.length() {
             commands.entity(entity).despawn();
         } else {
-            transform.translation = fly.path.at(fly.progress)
+            transform.translation = fly.path.position(fly.distance)
+                + fly.lure_offset
                 + Vec3::new(
                     0.0,
                     if DAVID_DEBUG {
@@ -201,25 +210,29 @@ fn move_flying_insect(
                 )
                 + fly.break_free_position;
 
-            let tangent = fly.path.tangent_at(fly.progress).normalize();
+            let tangent = fly.path.direction(fly.distance);
             let up = Vec3::new(0.0, 1.0, 0.0);
-            let base_transform_mat = Mat3::from_cols(
-                Vec3::new(-1.0, 0.0, 0.0),
-                Vec3::new(0.0, 0.0, 1.0),
-                Vec3::new(0.0, 1.0, 0.0),
-            );
 
             transform.rotation = Quat::from_axis_angle(
                 Vec3::new(0.0, 0.0, 1.0),
                 ((PI / 2.0) * (2.0 * PI * time.elapsed_seconds() * 0.25).sin() - PI / 4.0) * 0.3,
-            ) * Quat::from_mat3(&base_transform_mat);
+            ) * insect_base_rotation();
         }
     }
 }
 
+/// turns the insect model to face along +z, the way insects fly through the scene
+pub fn insect_base_rotation() -> Quat {
+    Quat::from_mat3(&Mat3::from_cols(
+        Vec3::new(-1.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    ))
+}
+
 fn insect_ensnared_tick_cooking_and_free(
     mut commands: Commands,
-    mut web_query: Query<&mut Web>,
+    mut web_query: Query<&mut WebSimulation>,
     mut insect_query: Query<(&mut FlyingInsect, Entity), With<Ensnared>>,
     time: Res<Time>,
 ) {
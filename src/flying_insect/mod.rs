@@ -1,2 +1,4 @@
 pub mod flying_insect;
-pub mod fruit_fly;
+pub mod spawn;
+pub mod species;
+pub mod swarm;
@@ -0,0 +1,235 @@
+use crate::config::{GameConfig, COLLISION_GROUP_ENEMIES};
+use crate::flying_insect::flying_insect::{insect_base_rotation, FlyingInsect};
+use crate::flying_insect::species::{FlightPathKind, InsectSpecies};
+use crate::flying_insect::swarm::{Swarm, SwarmMember};
+use crate::ui::progress_bar::CookingInsect;
+use crate::waves::SpawnInsectEvent;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_health_bar3d::configuration::BarHeight;
+use bevy_health_bar3d::prelude::BarSettings;
+use bevy_rapier3d::geometry::{CollisionGroups, Group};
+use bevy_rapier3d::prelude::{ActiveCollisionTypes, ActiveEvents, Collider};
+use ld56_sim::flight_path::{ArcLengthPath, BezierCurve, CatmullRom, Polyline, ZigZag};
+use rand::Rng;
+
+pub const DAVID_DEBUG: bool = false;
+
+/// how far from the spawn point the members of a swarm start out
+const SWARM_SPREAD: f32 = 0.15;
+
+/// The animation graph for each species, made the first time one of them spawns
+#[derive(Resource, Default)]
+pub struct InsectAnimations {
+    pub graphs: HashMap<String, (Handle<AnimationGraph>, Vec<AnimationNodeIndex>)>,
+}
+
+pub fn spawn_insect(
+    mut commands: Commands,
+    asset_server: ResMut<AssetServer>,
+    mut ev_spawn: EventReader<SpawnInsectEvent>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+    mut animations: ResMut<InsectAnimations>,
+    config: Res<GameConfig>,
+) {
+    for SpawnInsectEvent(name) in ev_spawn.read() {
+        let Some(species) = config.insect_species.iter().find(|s| &s.name == name) else {
+            error!("No insect species called {}", name);
+            continue;
+        };
+
+        let mut rng = rand::thread_rng();
+        let x_begin = rng.gen_range(-4.0..0.0);
+        let x_end = rng.gen_range(-3.0..-1.0);
+        let y_begin = rng.gen_range(0.0..1.0);
+        let y_end = rng.gen_range(0.0..1.0);
+
+        let start_pos = Vec3::new(x_begin, y_begin, -2.0);
+        let end_pos = Vec3::new(x_end, y_end, 3.5);
+
+        let david_debug_pos = Vec2::new(-2.0, 0.1);
+
+        if !animations.graphs.contains_key(&species.name) {
+            let mut graph = AnimationGraph::new();
+            let clips: Vec<_> = graph
+                .add_clips(
+                    species
+                        .animation_clips
+                        .iter()
+                        .map(|clip| {
+                            GltfAssetLabel::Animation(*clip).from_asset(species.model.clone())
+                        })
+                        .map(|path| asset_server.load(path)),
+                    1.0,
+                    graph.root,
+                )
+                .collect();
+
+            animations
+                .graphs
+                .insert(species.name.clone(), (graphs.add(graph), clips));
+        }
+
+        let path = match species.flight_path {
+            _ if DAVID_DEBUG => ArcLengthPath::new(BezierCurve::new(
+                Vec3::new(david_debug_pos.x, david_debug_pos.y, -1.0),
+                Vec3::new(david_debug_pos.x, david_debug_pos.y, -1.0),
+                Vec3::new(david_debug_pos.x, david_debug_pos.y, 3.0),
+                Vec3::new(david_debug_pos.x, david_debug_pos.y, 3.0),
+            )),
+            kind => random_flight_path(kind, start_pos, end_pos, &mut rng),
+        };
+
+        match species.swarm_size {
+            Some((fewest, most)) => {
+                let count = rng.gen_range(fewest.min(most)..=fewest.max(most));
+                let swarm = commands
+                    .spawn(Swarm {
+                        path,
+                        speed: species.speed,
+                        distance: 0.0,
+                    })
+                    .id();
+                for _ in 0..count {
+                    let position = start_pos
+                        + Vec3::new(
+                            rng.gen_range(-1.0..1.0),
+                            rng.gen_range(-1.0..1.0),
+                            rng.gen_range(-1.0..1.0),
+                        ) * SWARM_SPREAD;
+                    // the swarm flies the path, its members just flock after it
+                    let path = ArcLengthPath::new(Polyline::new(vec![position]));
+                    let member = spawn_insect_entity(
+                        &mut commands,
+                        &asset_server,
+                        species,
+                        path,
+                        position,
+                        insect_base_rotation(),
+                    );
+                    commands.entity(member).insert(SwarmMember {
+                        swarm,
+                        velocity: Vec3::ZERO,
+                    });
+                }
+            }
+            None => {
+                spawn_insect_entity(
+                    &mut commands,
+                    &asset_server,
+                    species,
+                    path,
+                    start_pos,
+                    insect_base_rotation(),
+                );
+            }
+        }
+    }
+}
+
+/// One insect of `species` with its model and collider, ready to be caught in the web
+pub fn spawn_insect_entity(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    species: &InsectSpecies,
+    path: ArcLengthPath,
+    position: Vec3,
+    rotation: Quat,
+) -> Entity {
+    commands
+        .spawn((
+            FlyingInsect::new(species, path),
+            SceneBundle {
+                scene: asset_server
+                    .load(GltfAssetLabel::Scene(0).from_asset(species.model.clone())),
+                transform: Transform {
+                    translation: position,
+                    rotation,
+                    scale: Vec3::splat(species.scale),
+                },
+                global_transform: Default::default(),
+                visibility: Default::default(),
+                inherited_visibility: Default::default(),
+                view_visibility: Default::default(),
+            },
+            Collider::capsule_y(1.0, 1.0),
+            BarSettings::<CookingInsect> {
+                offset: 2.0,
+                width: 3.0,
+                height: BarHeight::Static(0.5),
+                ..default()
+            },
+        ))
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC)
+        .insert(CollisionGroups {
+            memberships: COLLISION_GROUP_ENEMIES,
+            filters: Group::ALL,
+        })
+        .id()
+}
+
+/// A random path of the given kind from `start` to `end`
+fn random_flight_path(
+    kind: FlightPathKind,
+    start: Vec3,
+    end: Vec3,
+    rng: &mut impl Rng,
+) -> ArcLengthPath {
+    match kind {
+        FlightPathKind::Bezier => {
+            ArcLengthPath::new(BezierCurve::random_from_endpoints(start, end, rng))
+        }
+        FlightPathKind::CatmullRom => {
+            ArcLengthPath::new(CatmullRom::random_through(start, end, 3, 0.6, rng))
+        }
+        FlightPathKind::ZigZag => ArcLengthPath::new(ZigZag::new(
+            start,
+            end,
+            rng.gen_range(0.15..0.4),
+            rng.gen_range(2.0..5.0),
+        )),
+        FlightPathKind::Hover => {
+            let centre = start.lerp(end, rng.gen_range(0.3..0.5));
+            ArcLengthPath::new(CatmullRom::hovering(
+                start,
+                centre,
+                rng.gen_range(0.2..0.4),
+                2,
+                end,
+            ))
+        }
+        FlightPathKind::Steering => ArcLengthPath::new(Polyline::steered(start, end, rng)),
+    }
+}
+
+/// Starts the flying animations of an insect once its model has loaded, using the clips of its
+/// species.
+pub fn fly_hentai_anime_setup(
+    mut commands: Commands,
+    animations: Res<InsectAnimations>,
+    mut player_query: Query<(Entity, &mut AnimationPlayer), Added<AnimationPlayer>>,
+    parent_query: Query<&Parent>,
+    insect_query: Query<&FlyingInsect>,
+) {
+    for (entity, mut player) in player_query.iter_mut() {
+        let Some(insect) = parent_query
+            .iter_ancestors(entity)
+            .find_map(|ancestor| insect_query.get(ancestor).ok())
+        else {
+            continue;
+        };
+        let Some((graph, clips)) = animations.graphs.get(&insect.species) else {
+            continue;
+        };
+
+        for clip in clips {
+            player.play(*clip).repeat();
+        }
+
+        commands
+            .entity(entity)
+            .insert(graph.clone())
+            .insert(player.clone());
+    }
+}
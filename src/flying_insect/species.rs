@@ -0,0 +1,144 @@
+use bevy::log::error;
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use serde::{Deserialize, Serialize};
+
+/// How an insect of a species flies across the scene
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FlightPathKind {
+    /// a random cubic Bézier curve between the spawn and exit points
+    Bezier,
+    /// a smooth curve through a few random points between the spawn and exit points
+    CatmullRom,
+    /// a straight line with a sideways zig-zag
+    ZigZag,
+    /// circles a spot for a while before flying on
+    Hover,
+    /// steers towards the exit point, wandering a little on the way
+    Steering,
+}
+
+/// One kind of insect, read from the `insect_species` list in `assets/game.config.ron`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InsectSpecies {
+    pub name: String,
+    /// glTF file in the assets folder
+    pub model: String,
+    /// animations in the glTF file that play together while it flies
+    pub animation_clips: Vec<usize>,
+    /// scale of the model, the collider scales with it
+    pub scale: f32,
+    /// world units it flies per second, the same on every kind of flight path
+    pub speed: f32,
+    /// mass it adds to the strand it gets caught on
+    pub weight: f32,
+    /// how hard it thrashes about once caught
+    pub struggle_strength: f32,
+    /// seconds the spider has to wrap it before it can be eaten
+    pub cook_time: f32,
+    /// food the spider gets from eating it
+    pub food: f32,
+    /// how often it spawns compared to the other species
+    pub spawn_weight: f32,
+    pub flight_path: FlightPathKind,
+    /// how strongly pumpkin lights draw it off its path, 0 ignores them
+    #[serde(default)]
+    pub light_attraction: f32,
+    /// comes as a flocking swarm of between the two numbers of insects, alone if `None`
+    #[serde(default)]
+    pub swarm_size: Option<(u32, u32)>,
+}
+
+impl InsectSpecies {
+    pub fn defaults() -> Vec<InsectSpecies> {
+        vec![
+            InsectSpecies {
+                name: "fruit fly".to_string(),
+                model: "fruit_fly.glb".to_string(),
+                animation_clips: vec![0, 1],
+                scale: 0.03,
+                speed: 0.55,
+                weight: 0.01,
+                struggle_strength: 1.0,
+                cook_time: 5.0,
+                food: 1.75,
+                spawn_weight: 6.0,
+                flight_path: FlightPathKind::ZigZag,
+                light_attraction: 0.0,
+                swarm_size: None,
+            },
+            InsectSpecies {
+                name: "moth".to_string(),
+                model: "fruit_fly.glb".to_string(),
+                animation_clips: vec![0, 1],
+                scale: 0.05,
+                speed: 0.4,
+                weight: 0.02,
+                struggle_strength: 1.5,
+                cook_time: 6.0,
+                food: 2.5,
+                spawn_weight: 2.0,
+                flight_path: FlightPathKind::Hover,
+                light_attraction: 1.0,
+                swarm_size: None,
+            },
+            InsectSpecies {
+                name: "beetle".to_string(),
+                model: "fruit_fly.glb".to_string(),
+                animation_clips: vec![0, 1],
+                scale: 0.045,
+                speed: 0.28,
+                weight: 0.05,
+                struggle_strength: 3.0,
+                cook_time: 8.0,
+                food: 4.0,
+                spawn_weight: 1.0,
+                flight_path: FlightPathKind::CatmullRom,
+                light_attraction: 0.0,
+                swarm_size: None,
+            },
+            InsectSpecies {
+                name: "wasp".to_string(),
+                model: "fruit_fly.glb".to_string(),
+                animation_clips: vec![0, 1],
+                scale: 0.04,
+                speed: 0.8,
+                weight: 0.03,
+                struggle_strength: 4.0,
+                cook_time: 7.0,
+                food: 3.0,
+                spawn_weight: 0.5,
+                flight_path: FlightPathKind::Steering,
+                light_attraction: 0.0,
+                swarm_size: None,
+            },
+            InsectSpecies {
+                name: "gnat".to_string(),
+                model: "fruit_fly.glb".to_string(),
+                animation_clips: vec![0, 1],
+                scale: 0.015,
+                speed: 0.45,
+                weight: 0.003,
+                struggle_strength: 0.3,
+                cook_time: 2.0,
+                food: 0.4,
+                spawn_weight: 1.0,
+                flight_path: FlightPathKind::Steering,
+                light_attraction: 0.0,
+                swarm_size: Some((10, 30)),
+            },
+        ]
+    }
+}
+
+/// Picks a species at random by `spawn_weight`, `None` if no species can spawn.
+pub fn choose_species(species: &[InsectSpecies]) -> Option<&InsectSpecies> {
+    let weights = species.iter().map(|s| s.spawn_weight.max(0.0));
+    match WeightedIndex::new(weights) {
+        Ok(index) => Some(&species[index.sample(&mut rand::thread_rng())]),
+        Err(err) => {
+            error!("Can't pick an insect species to spawn: {}", err);
+            None
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::game::GameRestarted;
+use crate::web::ensnare::Ensnared;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use ld56_sim::flight_path::ArcLengthPath;
+use ld56_sim::swarm::{step_boids, Boid, BoidRules};
+
+/// how far ahead of the swarm's place on its path the members aim
+const GOAL_LEAD: f32 = 0.4;
+/// how much faster than the swarm members can fly, to catch up with it
+const CATCH_UP_SPEED: f32 = 1.6;
+
+/// A group of insects flocking along a path together, its members have a `SwarmMember` pointing
+/// back at it. The path is flown here instead of by each member.
+#[derive(Component)]
+pub struct Swarm {
+    pub path: ArcLengthPath,
+    /// world units per second
+    pub speed: f32,
+    /// how far along its path it has flown
+    pub distance: f32,
+}
+
+#[derive(Component)]
+pub struct SwarmMember {
+    pub swarm: Entity,
+    pub velocity: Vec3,
+}
+
+fn boid_rules(speed: f32) -> BoidRules {
+    BoidRules {
+        separation_radius: 0.05,
+        neighbour_radius: 0.15,
+        separation: 6.0,
+        alignment: 1.0,
+        cohesion: 2.0,
+        goal: 1.5,
+        max_speed: speed * CATCH_UP_SPEED,
+        max_force: 3.0,
+    }
+}
+
+/// Moves each swarm along its path and flocks its free members after it. At the end of the path
+/// the swarm and whoever is still flying with it are gone, as are members left without a swarm.
+pub fn move_swarms(
+    mut commands: Commands,
+    mut swarm_query: Query<(Entity, &mut Swarm)>,
+    mut member_query: Query<(Entity, &mut SwarmMember, &mut Transform), Without<Ensnared>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+
+    let mut flocks: HashMap<Entity, Vec<(Entity, Boid)>> = HashMap::new();
+    for (entity, member, transform) in &member_query {
+        flocks.entry(member.swarm).or_default().push((
+            entity,
+            Boid {
+                position: transform.translation,
+                velocity: member.velocity,
+            },
+        ));
+    }
+
+    for (swarm_entity, mut swarm) in &mut swarm_query {
+        swarm.distance += dt * swarm.speed;
+        let members = flocks.remove(&swarm_entity).unwrap_or_default();
+
+        if swarm.distance > swarm.path.length() {
+            commands.entity(swarm_entity).despawn();
+            for (entity, _) in members {
+                commands.entity(entity).despawn_recursive();
+            }
+            continue;
+        }
+
+        let goal = swarm.path.position(swarm.distance + GOAL_LEAD);
+        let mut boids: Vec<Boid> = members.iter().map(|(_, boid)| *boid).collect();
+        step_boids(&mut boids, goal, &boid_rules(swarm.speed), dt);
+
+        for ((entity, _), boid) in members.iter().zip(boids) {
+            if let Ok((_, mut member, mut transform)) = member_query.get_mut(*entity) {
+                member.velocity = boid.velocity;
+                transform.translation = boid.position;
+            }
+        }
+    }
+
+    for (_, members) in flocks {
+        for (entity, _) in members {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// despawns every swarm, `clear_insects` takes care of the members
+pub fn clear_swarms(
+    mut commands: Commands,
+    mut ev_restarted: EventReader<GameRestarted>,
+    swarm_query: Query<Entity, With<Swarm>>,
+) {
+    if ev_restarted.read().count() == 0 {
+        return;
+    }
+
+    for entity in &swarm_query {
+        commands.entity(entity).despawn();
+    }
+}
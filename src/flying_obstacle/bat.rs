@@ -0,0 +1,77 @@
+use crate::config::{COLLISION_GROUP_ENEMIES, COLLISION_GROUP_WALLS};
+use crate::flying_obstacle::flying_obstacle::{
+    FlyingObstacle, ObstacleKind, ObstacleMotion, SwoopMotion, WebEffect,
+};
+use crate::waves::SpawnObstacleEvent;
+use bevy::prelude::*;
+use bevy_rapier3d::geometry::ActiveEvents;
+use bevy_rapier3d::prelude::{ActiveCollisionTypes, Collider, CollisionGroups};
+use ld56_sim::flight_path::{ArcLengthPath, CatmullRom};
+use rand::Rng;
+use std::time::Duration;
+
+/// strands a bat cuts before it is through the web
+const BAT_CUTS: u32 = 4;
+/// world units per second
+const BAT_SPEED: f32 = 2.5;
+
+#[derive(Component)]
+struct Bat;
+
+/// A bat that dives into the web, skims along it for a stretch, cutting strands as it goes, and
+/// flies out the other side.
+pub fn spawn_bat(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut ev_spawn: EventReader<SpawnObstacleEvent>,
+) {
+    for SpawnObstacleEvent(kind) in ev_spawn.read() {
+        if *kind != ObstacleKind::Bat {
+            continue;
+        }
+        debug!("A bat swoops down");
+        let mut rng = rand::thread_rng();
+        let x_begin = rng.gen_range(-4.0..-2.0);
+        let skim_length = rng.gen_range(1.0..2.0);
+        let skim_height = rng.gen_range(0.2..0.8);
+        let x_end = x_begin + skim_length;
+
+        let path = ArcLengthPath::new(CatmullRom::new(vec![
+            Vec3::new(x_begin - 0.5, 2.0, -2.0),
+            Vec3::new(x_begin, skim_height + 0.2, 0.0),
+            Vec3::new((x_begin + x_end) / 2.0, skim_height, 0.0),
+            Vec3::new(x_end, skim_height + 0.2, 0.0),
+            Vec3::new(x_end + 0.5, 2.0, 2.5),
+        ]));
+        let start_pos = path.position(0.0);
+
+        commands
+            .spawn((
+                FlyingObstacle::with_motion(
+                    ObstacleMotion::Swoop(SwoopMotion {
+                        path,
+                        speed: BAT_SPEED,
+                    }),
+                    WebEffect::Cut(BAT_CUTS),
+                    Vec3::ZERO,
+                    Duration::from_secs(10),
+                ),
+                Bat,
+                // until it gets its own model
+                PbrBundle {
+                    mesh: meshes.add(Cuboid::new(0.2, 0.02, 0.06)),
+                    material: materials.add(Color::srgb(0.08, 0.05, 0.06)),
+                    transform: Transform::from_translation(start_pos),
+                    ..default()
+                },
+                Collider::cuboid(0.1, 0.02, 0.03),
+            ))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC)
+            .insert(CollisionGroups {
+                memberships: COLLISION_GROUP_ENEMIES,
+                filters: COLLISION_GROUP_WALLS,
+            });
+    }
+}
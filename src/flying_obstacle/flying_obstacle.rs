@@ -1,32 +1,86 @@
+use crate::flying_obstacle::bat::spawn_bat;
+use crate::flying_obstacle::leaf::spawn_leaf;
+use crate::flying_obstacle::raindrop::spawn_raindrops;
 use crate::flying_obstacle::rock::spawn_rock;
+use crate::game::{GameRestarted, GameState};
 use bevy::app::{App, Plugin, Update};
 use bevy::math::EulerRot;
 use bevy::prelude::{
-    Commands, Component, Entity, Quat, Query, Res, Resource, Time, Timer, TimerMode, Transform,
-    Vec3,
+    in_state, Commands, Component, DespawnRecursiveExt, Entity, EventReader, IntoSystemConfigs,
+    Quat, Query, Res, Time, Transform, Vec3, With,
 };
+use ld56_sim::flight_path::ArcLengthPath;
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
 pub struct FlyingObstaclePlugin;
 
-pub const ROCK_TIMER_START: f32 = 15000.0;
-pub const ROCK_TIMER_MULTIPLIER: f32 = 0.05;
-
-#[derive(Resource)]
-pub struct RockSpawnTimer {
-    pub timer: Timer,
+/// The kinds of thing thrown at the web, picked by the waves in `assets/game.waves.ron`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObstacleKind {
+    Rock,
+    /// flutters down slowly, nudging the strands it brushes past
+    Leaf,
+    /// a burst of drops that soak the web and weigh it down
+    Raindrops,
+    /// swoops along the web, cutting several strands
+    Bat,
+    /// hunts caught insects and cuts the web, see `PredatorWasp`
+    PredatorWasp,
 }
 
 pub struct ParabolicMotion {
     pub start_pos: Vec3,
     pub velocity: Vec3,
     pub gravity: Vec3,
-    pub time: f32,
+}
+
+/// Falling against air resistance, swaying from side to side
+pub struct FlutterMotion {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub gravity: Vec3,
+    /// steady push from the wind
+    pub wind: Vec3,
+    /// how quickly the air slows it, it falls no faster than `(gravity + wind) / drag`
+    pub drag: f32,
+    /// sideways push of the sway
+    pub sway: f32,
+    /// sways per second, in radians
+    pub sway_frequency: f32,
+    pub sway_phase: f32,
+}
+
+/// Flying along a curve at a steady speed
+pub struct SwoopMotion {
+    pub path: ArcLengthPath,
+    /// world units per second
+    pub speed: f32,
+}
+
+pub enum ObstacleMotion {
+    Parabolic(ParabolicMotion),
+    Flutter(FlutterMotion),
+    Swoop(SwoopMotion),
+}
+
+/// What an obstacle does to a strand it hits
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WebEffect {
+    /// cuts through, until it has cut this many times
+    Cut(u32),
+    /// pushes the strand down this hard and carries on
+    Push(f32),
+    /// leaves this much water on the strand and is gone
+    Soak(f32),
 }
 
 #[derive(Component)]
 pub struct FlyingObstacle {
-    pub motion: ParabolicMotion,
+    pub motion: ObstacleMotion,
+    pub effect: WebEffect,
+    /// seconds since it was spawned
+    pub time: f32,
     pub despawn_duration: Duration,
     pub spin: Vec3,
 }
@@ -39,33 +93,57 @@ impl FlyingObstacle {
         spin: Vec3,
         lifespan: Duration,
     ) -> Self {
-        FlyingObstacle {
-            motion: ParabolicMotion {
+        FlyingObstacle::with_motion(
+            ObstacleMotion::Parabolic(ParabolicMotion {
                 start_pos,
                 velocity,
                 gravity,
-                time: 0.0,
-            },
+            }),
+            WebEffect::Cut(1),
+            spin,
+            lifespan,
+        )
+    }
+
+    pub fn with_motion(
+        motion: ObstacleMotion,
+        effect: WebEffect,
+        spin: Vec3,
+        lifespan: Duration,
+    ) -> Self {
+        FlyingObstacle {
+            motion,
+            effect,
+            time: 0.0,
             despawn_duration: lifespan,
             spin,
         }
     }
 }
 
-pub(crate) fn rock_timer_value(t: f32) -> f32 {
-    (ROCK_TIMER_START / (ROCK_TIMER_MULTIPLIER * t + 1.0)) / 1000.0
-}
-
 impl Plugin for FlyingObstaclePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, move_obstacle);
-        app.add_systems(Update, spawn_rock);
-        app.insert_resource(RockSpawnTimer {
-            timer: Timer::new(
-                Duration::from_secs_f32(rock_timer_value(0.0)),
-                TimerMode::Repeating,
-            ),
-        });
+        app.add_systems(Update, move_obstacle.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, spawn_rock.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, spawn_leaf.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, spawn_raindrops.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, spawn_bat.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, clear_obstacles);
+    }
+}
+
+/// despawns every obstacle in the air
+fn clear_obstacles(
+    mut commands: Commands,
+    mut ev_restarted: EventReader<GameRestarted>,
+    obstacle_query: Query<Entity, With<FlyingObstacle>>,
+) {
+    if ev_restarted.read().count() == 0 {
+        return;
+    }
+
+    for entity in &obstacle_query {
+        commands.entity(entity).despawn_recursive();
     }
 }
 
@@ -74,29 +152,49 @@ fn move_obstacle(
     time: Res<Time>,
     mut query: Query<(&mut FlyingObstacle, &mut Transform, Entity)>,
 ) {
+    let dt = time.delta_seconds();
     for (mut obstacle, mut transform, entity) in query.iter_mut() {
-        obstacle.motion.time += time.delta_seconds() * 0.5;
-
-        // parabolic motion
-        transform.translation.x =
-            obstacle.motion.start_pos.x + obstacle.motion.velocity.x * obstacle.motion.time;
-        transform.translation.z =
-            obstacle.motion.start_pos.z + obstacle.motion.velocity.z * obstacle.motion.time;
-
-        transform.translation.y = obstacle.motion.start_pos.y
-            + obstacle.motion.velocity.y * obstacle.motion.time
-            + 0.5 * obstacle.motion.gravity.y * obstacle.motion.time * obstacle.motion.time;
+        obstacle.time += dt;
+        let t = obstacle.time;
 
         // rotation tumble
         transform.rotation = Quat::from_euler(
             EulerRot::XYZ,
-            obstacle.spin.x * obstacle.motion.time,
-            obstacle.spin.y * obstacle.motion.time,
-            obstacle.spin.z * obstacle.motion.time,
+            obstacle.spin.x * t,
+            obstacle.spin.y * t,
+            obstacle.spin.z * t,
         );
 
-        if obstacle.motion.time > obstacle.despawn_duration.as_secs_f32() {
-            commands.entity(entity).despawn();
+        match &mut obstacle.motion {
+            ObstacleMotion::Parabolic(motion) => {
+                transform.translation =
+                    motion.start_pos + motion.velocity * t + 0.5 * motion.gravity * t * t;
+            }
+            ObstacleMotion::Flutter(motion) => {
+                let sway =
+                    Vec3::X * motion.sway * (motion.sway_frequency * t + motion.sway_phase).sin();
+                let acceleration =
+                    motion.gravity + motion.wind + sway - motion.velocity * motion.drag;
+                motion.velocity += acceleration * dt;
+                motion.position += motion.velocity * dt;
+                transform.translation = motion.position;
+            }
+            ObstacleMotion::Swoop(motion) => {
+                let distance = t * motion.speed;
+                transform.translation = motion.path.position(distance);
+                if let Some(heading) = motion.path.direction(distance).try_normalize() {
+                    transform.rotation =
+                        Quat::from_rotation_arc(Vec3::Z, heading) * transform.rotation;
+                }
+                if distance > motion.path.length() {
+                    commands.entity(entity).despawn_recursive();
+                    continue;
+                }
+            }
+        }
+
+        if obstacle.time > obstacle.despawn_duration.as_secs_f32() {
+            commands.entity(entity).despawn_recursive();
         }
     }
 }
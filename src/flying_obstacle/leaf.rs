@@ -0,0 +1,66 @@
+use crate::config::{COLLISION_GROUP_ENEMIES, COLLISION_GROUP_WALLS};
+use crate::flying_obstacle::flying_obstacle::{
+    FlutterMotion, FlyingObstacle, ObstacleKind, ObstacleMotion, WebEffect,
+};
+use crate::waves::SpawnObstacleEvent;
+use bevy::prelude::*;
+use bevy_rapier3d::geometry::ActiveEvents;
+use bevy_rapier3d::prelude::{ActiveCollisionTypes, Collider, CollisionGroups};
+use rand::Rng;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+/// how hard a leaf pushes down the strand it brushes past
+const LEAF_PUSH: f32 = 1500.0;
+
+#[derive(Component)]
+struct Leaf;
+
+pub fn spawn_leaf(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut ev_spawn: EventReader<SpawnObstacleEvent>,
+) {
+    for SpawnObstacleEvent(kind) in ev_spawn.read() {
+        if *kind != ObstacleKind::Leaf {
+            continue;
+        }
+        debug!("A leaf is falling");
+        let mut rng = rand::thread_rng();
+        let start_pos = Vec3::new(rng.gen_range(-3.5..-0.5), rng.gen_range(2.0..2.5), -1.0);
+
+        commands
+            .spawn((
+                FlyingObstacle::with_motion(
+                    ObstacleMotion::Flutter(FlutterMotion {
+                        position: start_pos,
+                        velocity: Vec3::ZERO,
+                        gravity: Vec3::new(0.0, -1.2, 0.0),
+                        wind: Vec3::new(0.0, 0.0, 0.6),
+                        drag: 3.0,
+                        sway: rng.gen_range(1.0..2.0),
+                        sway_frequency: rng.gen_range(1.5..2.5),
+                        sway_phase: rng.gen_range(0.0..2.0 * PI),
+                    }),
+                    WebEffect::Push(LEAF_PUSH),
+                    Vec3::new(rng.gen_range(-2.0..2.0), rng.gen_range(-1.0..1.0), 0.5),
+                    Duration::from_secs(20),
+                ),
+                Leaf,
+                PbrBundle {
+                    mesh: meshes.add(Cuboid::new(0.08, 0.004, 0.05)),
+                    material: materials.add(Color::srgb(0.8, 0.35, 0.1)),
+                    transform: Transform::from_translation(start_pos),
+                    ..default()
+                },
+                Collider::cuboid(0.04, 0.01, 0.025),
+            ))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC)
+            .insert(CollisionGroups {
+                memberships: COLLISION_GROUP_ENEMIES,
+                filters: COLLISION_GROUP_WALLS,
+            });
+    }
+}
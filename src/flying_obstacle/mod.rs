@@ -1,2 +1,5 @@
+pub mod bat;
 pub mod flying_obstacle;
-pub mod rock;
\ No newline at end of file
+pub mod leaf;
+pub mod raindrop;
+pub mod rock;
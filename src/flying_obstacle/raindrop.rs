@@ -0,0 +1,76 @@
+use crate::config::{COLLISION_GROUP_ENEMIES, COLLISION_GROUP_WALLS};
+use crate::flying_obstacle::flying_obstacle::{FlyingObstacle, ObstacleKind, WebEffect};
+use crate::waves::SpawnObstacleEvent;
+use bevy::prelude::*;
+use bevy_rapier3d::geometry::ActiveEvents;
+use bevy_rapier3d::prelude::{ActiveCollisionTypes, Collider, CollisionGroups};
+use rand::Rng;
+use std::time::Duration;
+
+/// drops in one burst
+const BURST_SIZE: std::ops::RangeInclusive<u32> = 8..=16;
+/// water each drop leaves on the web
+const DROP_MASS: f32 = 0.004;
+
+#[derive(Component)]
+struct Raindrop;
+
+/// A burst of raindrops blown at the web. They don't cut it, each leaves a little water that
+/// weighs the strands down until it drips off.
+pub fn spawn_raindrops(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut ev_spawn: EventReader<SpawnObstacleEvent>,
+) {
+    for SpawnObstacleEvent(kind) in ev_spawn.read() {
+        if *kind != ObstacleKind::Raindrops {
+            continue;
+        }
+        debug!("It's raining");
+        let mut rng = rand::thread_rng();
+        let mesh = meshes.add(Sphere::new(0.01));
+        let material = materials.add(StandardMaterial {
+            base_color: Color::srgba(0.6, 0.75, 1.0, 0.6),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        });
+
+        for _ in 0..rng.gen_range(BURST_SIZE) {
+            // further back means later, so the burst comes down over a second or so
+            let start_pos = Vec3::new(
+                rng.gen_range(-3.5..-0.5),
+                rng.gen_range(3.0..3.5),
+                rng.gen_range(-2.5..-1.0),
+            );
+
+            commands
+                .spawn((
+                    FlyingObstacle {
+                        effect: WebEffect::Soak(DROP_MASS),
+                        ..FlyingObstacle::new(
+                            start_pos,
+                            Vec3::new(0.0, -1.0, 1.5),
+                            Vec3::new(0.0, -9.81, 0.0),
+                            Vec3::ZERO,
+                            Duration::from_secs(5),
+                        )
+                    },
+                    Raindrop,
+                    PbrBundle {
+                        mesh: mesh.clone(),
+                        material: material.clone(),
+                        transform: Transform::from_translation(start_pos),
+                        ..default()
+                    },
+                    Collider::ball(0.01),
+                ))
+                .insert(ActiveEvents::COLLISION_EVENTS)
+                .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC)
+                .insert(CollisionGroups {
+                    memberships: COLLISION_GROUP_ENEMIES,
+                    filters: COLLISION_GROUP_WALLS,
+                });
+        }
+    }
+}
@@ -1,6 +1,8 @@
-use crate::health::HealthBar;
+use crate::controls::{ControlAction, ControlBindings, ControlState};
+use crate::health::{HealthBar, SilkBar};
 use crate::mesh_loader::{self, load_level, MeshLoader};
 use crate::skybox::{Cubemap, CUBEMAPS};
+use crate::tree::GameStart;
 use crate::web::WebSimulationPlugin;
 use bevy::asset::LoadState;
 use bevy::audio::PlaybackMode::Loop;
@@ -24,12 +26,47 @@ pub struct GamePlugin;
 pub enum GameState {
     #[default]
     TitleScreen,
+    /// the best runs, opened from the title screen
+    HighScores,
     Game,
+    /// game time is frozen until the player resumes
+    Paused,
+    /// the spider starved, waiting for the player to restart
+    GameOver,
+    /// rebinding controls, opened from the title screen or the pause menu
+    ControlsMenu,
 }
 
+/// Sent when the player starts a new game after a game over or from the pause menu. Every
+/// plugin despawns and resets its own part of the game when it sees this.
+#[derive(Event)]
+pub struct GameRestarted;
+
+/// marks the text shown while paused or after a game over
+#[derive(Component)]
+struct StateOverlay;
+
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.insert_state(GameState::TitleScreen);
+        app.add_event::<GameRestarted>();
+        app.add_systems(
+            Update,
+            toggle_pause.run_if(in_state(GameState::Game).or_else(in_state(GameState::Paused))),
+        );
+        app.add_systems(
+            Update,
+            restart_game.run_if(in_state(GameState::Paused).or_else(in_state(GameState::GameOver))),
+        );
+        app.add_systems(OnEnter(GameState::Paused), (pause_time, show_pause_overlay));
+        app.add_systems(
+            OnExit(GameState::Paused),
+            (unpause_time, hide_state_overlay),
+        );
+        app.add_systems(OnEnter(GameState::ControlsMenu), pause_time);
+        app.add_systems(OnExit(GameState::ControlsMenu), unpause_time);
+        app.add_systems(OnEnter(GameState::GameOver), show_game_over_overlay);
+        app.add_systems(OnExit(GameState::GameOver), hide_state_overlay);
         app.add_systems(Startup, setup.after(mesh_loader::setup));
         app.add_systems(
             Update,
@@ -225,6 +262,38 @@ fn setup(
                         },
                     ));
                 });
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(200.0),
+                        height: Val::Px(20.0),
+                        position_type: PositionType::Absolute,
+                        right: Val::Px(240.),
+                        bottom: Val::Px(20.),
+                        border: UiRect::all(Val::Px(2.)),
+                        ..default()
+                    },
+                    border_color: BLACK.into(),
+                    background_color: Color::srgb(0.4, 0.4, 0.4).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        SilkBar,
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(100.0),
+                                height: Val::Percent(100.0),
+                                position_type: PositionType::Relative,
+                                left: Val::Px(0.0),
+                                ..default()
+                            },
+                            background_color: Color::srgb(0.9, 0.9, 0.8).into(),
+                            ..default()
+                        },
+                    ));
+                });
         });
 
     commands.insert_resource(Cubemap {
@@ -269,8 +338,8 @@ fn get_initial_sun_transform() -> Transform {
         .looking_at(Vec3::ZERO, Vec3::Y)
 }
 
-fn debug_render_toggle(mut context: ResMut<DebugRenderContext>, keys: Res<ButtonInput<KeyCode>>) {
-    if keys.just_released(KeyCode::F12) {
+fn debug_render_toggle(mut context: ResMut<DebugRenderContext>, controls: Res<ControlState>) {
+    if controls.just_pressed(ControlAction::ToggleDebug) {
         context.enabled = !context.enabled;
     }
 }
@@ -344,3 +413,113 @@ fn cycle_cubemap_asset(
     cubemap.image_handle = asset_server.load(CUBEMAPS[cubemap.index].0);
     cubemap.is_loaded = false;
 }
+
+fn toggle_pause(
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    controls: Res<ControlState>,
+) {
+    if controls.just_pressed(ControlAction::Pause) {
+        match state.get() {
+            GameState::Game => next_state.set(GameState::Paused),
+            GameState::Paused => next_state.set(GameState::Game),
+            _ => {}
+        }
+    }
+}
+
+/// restarts from the pause or game over screen, after a game over moving or tapping does too
+#[allow(clippy::too_many_arguments)]
+fn restart_game(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_restarted: EventWriter<GameRestarted>,
+    controls: Res<ControlState>,
+    touches: Res<Touches>,
+    time: Res<Time>,
+    start_query: Query<Entity, With<GameStart>>,
+) {
+    let clicked = controls.just_pressed(ControlAction::MoveTo) || touches.any_just_pressed();
+    let restart = controls.just_pressed(ControlAction::Restart)
+        || (*state.get() == GameState::GameOver && clicked);
+    if !restart {
+        return;
+    }
+
+    info!("Restarting game");
+    for entity in &start_query {
+        commands.entity(entity).despawn();
+    }
+    commands.spawn(GameStart {
+        game_start: time.elapsed_seconds(),
+    });
+
+    ev_restarted.send(GameRestarted);
+    next_state.set(GameState::Game);
+}
+
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unpause_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn spawn_state_overlay(commands: &mut Commands, text: &str) {
+    commands
+        .spawn((
+            StateOverlay,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.4).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                text,
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn show_pause_overlay(mut commands: Commands, bindings: Res<ControlBindings>) {
+    spawn_state_overlay(
+        &mut commands,
+        &format!(
+            "Paused\n{} to resume, {} to restart, {} for controls",
+            bindings.label(ControlAction::Pause),
+            bindings.label(ControlAction::Restart),
+            bindings.label(ControlAction::ShowControls)
+        ),
+    );
+}
+
+fn show_game_over_overlay(mut commands: Commands, bindings: Res<ControlBindings>) {
+    spawn_state_overlay(
+        &mut commands,
+        &format!(
+            "Your spider starved\n{} to try again",
+            bindings.label(ControlAction::Restart)
+        ),
+    );
+}
+
+fn hide_state_overlay(mut commands: Commands, overlay_query: Query<Entity, With<StateOverlay>>) {
+    for entity in &overlay_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,3 +1,4 @@
+use crate::game::{GameRestarted, GameState};
 use crate::spider::{Spider, SpiderFeastEvent};
 use crate::tree::{
     get_death_target_position, get_death_target_rotation, get_target_camera_direction,
@@ -5,7 +6,8 @@ use crate::tree::{
 };
 use bevy::app::{App, Plugin, Update};
 use bevy::prelude::{
-    Camera, Component, EventReader, Query, Res, ResMut, Resource, Style, Time, Without,
+    in_state, Camera, Component, EventReader, IntoSystemConfigs, NextState, Query, Res, ResMut,
+    Resource, Style, Time, With, Without,
 };
 use bevy::ui::Val;
 
@@ -13,8 +15,11 @@ pub struct HealthPlugin;
 impl Plugin for HealthPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Update, update_health);
+        app.add_systems(Update, update_silk_bar);
         app.add_systems(Update, update_death_screen);
         app.add_systems(Update, feast_on_bugs);
+        app.add_systems(Update, starve.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, reset_death);
         app.add_event::<SpiderFeastEvent>();
         app.insert_resource(IsDead {
             is_dead: false,
@@ -26,6 +31,10 @@ impl Plugin for HealthPlugin {
 #[derive(Component)]
 pub struct HealthBar;
 
+/// fill of the bar showing how much silk the spider has left
+#[derive(Component)]
+pub struct SilkBar;
+
 #[derive(Resource)]
 pub struct IsDead {
     pub is_dead: bool,
@@ -42,6 +51,18 @@ fn update_health(
     style.width = Val::Percent((100.0 * (spider.food / spider.max_food)) as f32);
 }
 
+fn update_silk_bar(
+    spider_query: Query<&Spider, Without<SilkBar>>,
+    mut silk_query: Query<&mut Style, With<SilkBar>>,
+) {
+    let (Ok(spider), Ok(mut style)) = (spider_query.get_single(), silk_query.get_single_mut())
+    else {
+        return;
+    };
+
+    style.width = Val::Percent(100.0 * (spider.silk / spider.max_silk));
+}
+
 fn update_death_screen(
     mut is_dead: ResMut<IsDead>,
     mut camera_transform_query: Query<(&mut bevy::prelude::Transform, &Camera)>,
@@ -75,3 +96,16 @@ fn feast_on_bugs(
         spider.food = spider.max_food.min(spider.food + food);
     }
 }
+
+fn starve(is_dead: Res<IsDead>, mut next_state: ResMut<NextState<GameState>>) {
+    if is_dead.is_dead {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn reset_death(mut ev_restarted: EventReader<GameRestarted>, mut is_dead: ResMut<IsDead>) {
+    if ev_restarted.read().count() > 0 {
+        is_dead.is_dead = false;
+        is_dead.death_camera_progress = 0.0;
+    }
+}
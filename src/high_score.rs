@@ -0,0 +1,99 @@
+use crate::game::GameState;
+use crate::score::RunStats;
+use crate::storage::{read_text, write_text, StorageError};
+use bevy::prelude::*;
+use bevy::utils::SystemTime;
+use ld56_sim::high_score::{HighScoreEntry, HighScoreTable, HIGH_SCORE_VERSION};
+
+/// File name in the platform data dir on desktop, key in `localStorage` on the web.
+pub const HIGH_SCORE_FILE: &str = "high_scores.ron";
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_high_score_table);
+        app.add_systems(OnEnter(GameState::GameOver), record_high_score);
+    }
+}
+
+/// The high score table, wrapped so it can be a resource
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct HighScores(pub HighScoreTable);
+
+#[derive(Debug)]
+pub enum HighScoreError {
+    Storage(StorageError),
+    Serialize(ron::Error),
+    Parse(ron::error::SpannedError),
+    UnsupportedVersion(u32),
+}
+
+impl std::fmt::Display for HighScoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HighScoreError::Storage(err) => write!(f, "{}", err),
+            HighScoreError::Serialize(err) => write!(f, "{}", err),
+            HighScoreError::Parse(err) => write!(f, "{}", err),
+            HighScoreError::UnsupportedVersion(version) => write!(
+                f,
+                "high score version {} is newer than {}",
+                version, HIGH_SCORE_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HighScoreError {}
+
+pub fn load_high_scores() -> Result<HighScoreTable, HighScoreError> {
+    let Some(text) = read_text(HIGH_SCORE_FILE).map_err(HighScoreError::Storage)? else {
+        return Ok(Default::default());
+    };
+    let table: HighScoreTable = ron::from_str(&text).map_err(HighScoreError::Parse)?;
+    if table.version > HIGH_SCORE_VERSION {
+        return Err(HighScoreError::UnsupportedVersion(table.version));
+    }
+    Ok(table)
+}
+
+pub fn save_high_scores(table: &HighScoreTable) -> Result<(), HighScoreError> {
+    let text = ron::ser::to_string_pretty(table, ron::ser::PrettyConfig::default())
+        .map_err(HighScoreError::Serialize)?;
+    write_text(HIGH_SCORE_FILE, &text).map_err(HighScoreError::Storage)
+}
+
+fn load_high_score_table(mut commands: Commands) {
+    let table = match load_high_scores() {
+        Ok(table) => table,
+        Err(err) => {
+            error!("Could not load high scores: {}", err);
+            Default::default()
+        }
+    };
+    info!("Loaded {} high scores", table.entries.len());
+    commands.insert_resource(HighScores(table));
+}
+
+fn record_high_score(stats: Res<RunStats>, mut table: ResMut<HighScores>) {
+    let date = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    let kept = table.insert(HighScoreEntry {
+        date,
+        survival_time: stats.survival_time,
+        insects_eaten: stats.insects_eaten,
+        silk_used: stats.silk_used,
+        score: stats.score(),
+    });
+    if !kept {
+        return;
+    }
+
+    info!("Run made the high score table");
+    if let Err(err) = save_high_scores(&table) {
+        error!("Could not save high scores: {}", err);
+    }
+}
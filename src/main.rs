@@ -1,11 +1,18 @@
+use crate::config::GameConfigPlugin;
+use crate::controls::ControlsPlugin;
 use crate::flying_insect::flying_insect::FlyingInsectPlugin;
 use crate::flying_obstacle::flying_obstacle::FlyingObstaclePlugin;
 use crate::game::GamePlugin;
 use crate::health::HealthPlugin;
+use crate::high_score::HighScorePlugin;
 use crate::mesh_loader::MeshLoaderPlugin;
+use crate::predator::PredatorPlugin;
+use crate::score::ScorePlugin;
 use crate::spider::SpiderPlugin;
 use crate::title_screen::TitleScreenPlugin;
+use crate::ui::controls_menu::ControlsMenuPlugin;
 use crate::ui::progress_bar::ProgressBarPlugin;
+use crate::waves::WavesPlugin;
 use bevy::app::{App, PluginGroup};
 use bevy::asset::AssetMetaCheck;
 use bevy::prelude::*;
@@ -17,16 +24,22 @@ use pumpkin::PumpkinPlugin;
 use tree::TreePlugin;
 
 mod config;
+mod controls;
 mod game;
 mod mesh_loader;
+mod predator;
 mod pumpkin;
+mod score;
 mod spider;
+mod storage;
 mod tree;
+mod waves;
 mod web;
 
 mod flying_insect;
 mod flying_obstacle;
 mod health;
+mod high_score;
 mod skybox;
 mod title_screen;
 mod ui;
@@ -71,6 +84,9 @@ fn main() {
         );
     }
 
+    app.add_plugins(GameConfigPlugin);
+    app.add_plugins(WavesPlugin);
+    app.add_plugins(ControlsPlugin);
     app.add_plugins(TitleScreenPlugin);
     app.add_plugins(GamePlugin);
     app.add_plugins(MeshLoaderPlugin);
@@ -79,8 +95,12 @@ fn main() {
     app.add_plugins(SpiderPlugin);
     app.add_plugins(FlyingInsectPlugin);
     app.add_plugins(HealthPlugin);
+    app.add_plugins(ScorePlugin);
+    app.add_plugins(HighScorePlugin);
     app.add_plugins(FlyingObstaclePlugin);
+    app.add_plugins(PredatorPlugin);
     app.add_plugins(ProgressBarPlugin);
+    app.add_plugins(ControlsMenuPlugin);
 
     app.run();
 }
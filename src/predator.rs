@@ -0,0 +1,354 @@
+use crate::config::COLLISION_GROUP_ENEMIES;
+use crate::flying_insect::flying_insect::{insect_base_rotation, FlyingInsect};
+use crate::flying_obstacle::flying_obstacle::ObstacleKind;
+use crate::game::{GameRestarted, GameState};
+use crate::spider::Spider;
+use crate::waves::SpawnObstacleEvent;
+use crate::web::ensnare::{free_enemy_from_web, Ensnared};
+use crate::web::render::WebSegmentCollision;
+use crate::web::{WebCutCause, WebCutEvent, WebSimulation};
+use bevy::prelude::*;
+use bevy_rapier3d::geometry::{CollisionGroups, Group};
+use bevy_rapier3d::pipeline::CollisionEvent;
+use bevy_rapier3d::prelude::{ActiveCollisionTypes, ActiveEvents, Collider};
+use rand::Rng;
+
+/// world units per second
+const WASP_SPEED: f32 = 0.9;
+/// how quickly it turns, higher is sharper
+const WASP_TURN_RATE: f32 = 3.0;
+/// slows down this close to where it is going, so it doesn't overshoot its prey
+const WASP_ARRIVE_DISTANCE: f32 = 0.3;
+/// how close it has to get to a caught insect to carry it off
+const STEAL_RADIUS: f32 = 0.06;
+/// seconds it looks for prey before giving up
+const HUNT_TIME: f32 = 15.0;
+/// how much faster than usual it flees once the spider touches it
+const DRIVEN_OFF_SPEED: f32 = 2.0;
+/// how far away from the spider it flees before it is gone
+const DRIVEN_OFF_DISTANCE: f32 = 4.0;
+/// gone once it is this close to where it leaves the scene
+const EXIT_RADIUS: f32 = 0.2;
+
+pub struct PredatorPlugin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaspState {
+    /// circling the web, going for the nearest caught insect
+    Hunting,
+    /// done, with or without prey, and flying off
+    Leaving,
+}
+
+/// A wasp that steals caught insects off the web and cuts the strands it flies into. The spider
+/// drives it off by touching it.
+#[derive(Component)]
+pub struct PredatorWasp {
+    pub state: WaspState,
+    pub velocity: Vec3,
+    /// the caught insect it is going for
+    pub prey: Option<Entity>,
+    /// seconds spent hunting
+    pub hunt_time: f32,
+    /// where it circles while there is nothing to steal
+    pub patrol: Vec3,
+    /// where it leaves the scene, away from the spider once driven off
+    pub exit: Vec3,
+    pub driven_off: bool,
+}
+
+/// A wasp carried off a caught insect
+#[derive(Event)]
+pub struct PreyStolenEvent;
+
+/// The spider drove a wasp off
+#[derive(Event)]
+pub struct WaspDrivenOffEvent;
+
+impl Plugin for PredatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PreyStolenEvent>();
+        app.add_event::<WaspDrivenOffEvent>();
+        app.add_systems(
+            Update,
+            spawn_predator_wasp.run_if(in_state(GameState::Game)),
+        );
+        app.add_systems(Update, hunt_with_wasps.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, wasps_cut_web.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, fight_off_wasps.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, clear_wasps);
+    }
+}
+
+fn spawn_predator_wasp(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut ev_spawn: EventReader<SpawnObstacleEvent>,
+) {
+    for SpawnObstacleEvent(kind) in ev_spawn.read() {
+        if *kind != ObstacleKind::PredatorWasp {
+            continue;
+        }
+        info!("A wasp is coming for the web");
+
+        let mut rng = rand::thread_rng();
+        let start_pos = Vec3::new(rng.gen_range(-4.0..0.0), rng.gen_range(0.5..1.5), -2.0);
+        let patrol = Vec3::new(rng.gen_range(-3.0..-1.0), rng.gen_range(0.2..0.8), -0.3);
+        let exit = Vec3::new(rng.gen_range(-4.0..0.0), rng.gen_range(1.0..2.0), 4.0);
+
+        commands
+            .spawn((
+                PredatorWasp {
+                    state: WaspState::Hunting,
+                    velocity: Vec3::ZERO,
+                    prey: None,
+                    hunt_time: 0.0,
+                    patrol,
+                    exit,
+                    driven_off: false,
+                },
+                SceneBundle {
+                    scene: asset_server.load(GltfAssetLabel::Scene(0).from_asset("fruit_fly.glb")),
+                    transform: Transform {
+                        translation: start_pos,
+                        rotation: insect_base_rotation(),
+                        scale: Vec3::splat(0.05),
+                    },
+                    global_transform: Default::default(),
+                    visibility: Default::default(),
+                    inherited_visibility: Default::default(),
+                    view_visibility: Default::default(),
+                },
+                Collider::ball(1.0),
+            ))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC)
+            .insert(CollisionGroups {
+                memberships: COLLISION_GROUP_ENEMIES,
+                filters: Group::ALL,
+            });
+    }
+}
+
+/// Flies the wasps after the nearest caught insect and carries it off once close enough,
+/// circling the web while there is none. Leaving wasps fly off and are gone.
+fn hunt_with_wasps(
+    mut commands: Commands,
+    mut wasp_query: Query<(Entity, &mut PredatorWasp, &mut Transform), Without<FlyingInsect>>,
+    prey_query: Query<(Entity, &FlyingInsect, &Transform), With<Ensnared>>,
+    mut web_query: Query<&mut WebSimulation>,
+    mut spider_query: Query<&mut Spider>,
+    mut ev_stolen: EventWriter<PreyStolenEvent>,
+    time: Res<Time>,
+) {
+    let Ok(mut web) = web_query.get_single_mut() else {
+        error!("ERROR NO WEB OR MORE THAN ONE WEB");
+        return;
+    };
+    let dt = time.delta_seconds();
+    // taken this frame, the despawn only happens after the system
+    let mut stolen: Vec<Entity> = vec![];
+
+    for (wasp_entity, mut wasp, mut transform) in &mut wasp_query {
+        let position = transform.translation;
+
+        if wasp.state == WaspState::Hunting {
+            wasp.hunt_time += dt;
+            if wasp.hunt_time > HUNT_TIME {
+                debug!("A wasp gave up hunting");
+                wasp.state = WaspState::Leaving;
+                wasp.prey = None;
+            }
+        }
+
+        if wasp.state == WaspState::Hunting
+            && wasp
+                .prey
+                .is_none_or(|prey| !prey_query.contains(prey) || stolen.contains(&prey))
+        {
+            wasp.prey = prey_query
+                .iter()
+                .filter(|(entity, _, _)| !stolen.contains(entity))
+                .min_by(|(_, _, a), (_, _, b)| {
+                    a.translation
+                        .distance_squared(position)
+                        .total_cmp(&b.translation.distance_squared(position))
+                })
+                .map(|(entity, _, _)| entity);
+        }
+
+        let target = match (
+            wasp.state,
+            wasp.prey.and_then(|prey| prey_query.get(prey).ok()),
+        ) {
+            (WaspState::Hunting, Some((prey, insect, prey_transform))) => {
+                if prey_transform.translation.distance(position) < STEAL_RADIUS {
+                    info!("A wasp carried off a {}", insect.species);
+                    free_enemy_from_web(&mut commands, prey, Some(insect), &mut web);
+                    commands.entity(prey).despawn_recursive();
+                    for mut spider in &mut spider_query {
+                        spider.touching_insects.retain(|touching| *touching != prey);
+                    }
+                    ev_stolen.send(PreyStolenEvent);
+                    stolen.push(prey);
+                    wasp.state = WaspState::Leaving;
+                    wasp.prey = None;
+                    wasp.exit
+                } else {
+                    prey_transform.translation
+                }
+            }
+            (WaspState::Hunting, None) => {
+                let angle = wasp.hunt_time * 1.5;
+                wasp.patrol + Vec3::new(angle.cos(), 0.3 * (2.0 * angle).sin(), angle.sin()) * 0.3
+            }
+            (WaspState::Leaving, _) => wasp.exit,
+        };
+
+        if wasp.state == WaspState::Leaving && position.distance(wasp.exit) < EXIT_RADIUS {
+            commands.entity(wasp_entity).despawn_recursive();
+            continue;
+        }
+
+        let to_target = target - position;
+        let top_speed = if wasp.driven_off {
+            WASP_SPEED * DRIVEN_OFF_SPEED
+        } else {
+            WASP_SPEED
+        };
+        let speed = top_speed * (to_target.length() / WASP_ARRIVE_DISTANCE).min(1.0);
+        let wanted_velocity = to_target.normalize_or_zero() * speed;
+        let turn = (dt * WASP_TURN_RATE).min(1.0);
+        wasp.velocity = wasp.velocity.lerp(wanted_velocity, turn);
+
+        transform.translation += wasp.velocity * dt;
+        if let Some(heading) = wasp.velocity.try_normalize() {
+            transform.rotation = Quat::from_rotation_arc(Vec3::Z, heading) * insect_base_rotation();
+        }
+    }
+}
+
+/// Cuts the strands wasps fly into, except the one holding the insect a wasp is landing on.
+fn wasps_cut_web(
+    mut commands: Commands,
+    mut web_query: Query<&mut WebSimulation>,
+    insect_query: Query<&FlyingInsect>,
+    wasp_query: Query<(&PredatorWasp, &Transform)>,
+    web_segment_collisions_query: Query<&WebSegmentCollision>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut ev_cut: EventWriter<WebCutEvent>,
+) {
+    let Ok(mut web) = web_query.get_single_mut() else {
+        error!("ERROR NO WEB OR MORE THAN ONE WEB");
+        return;
+    };
+
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(entity_a, entity_b, _) = collision_event else {
+            continue;
+        };
+        let ((wasp, wasp_transform), web_segment) = match (
+            wasp_query.get(*entity_a),
+            wasp_query.get(*entity_b),
+            web_segment_collisions_query.get(*entity_a),
+            web_segment_collisions_query.get(*entity_b),
+        ) {
+            (Ok(wasp), Err(_), Err(_), Ok(web_segment)) => (wasp, web_segment),
+            (Err(_), Ok(wasp), Ok(web_segment), Err(_)) => (wasp, web_segment),
+            _ => continue,
+        };
+
+        // the spring may already be gone if something else cut it this frame
+        let Some(spring) = web.springs.get(web_segment.spring_index) else {
+            continue;
+        };
+        if wasp.state == WaspState::Hunting
+            && wasp.prey.is_some_and(|prey| {
+                spring
+                    .ensnared_entities
+                    .iter()
+                    .any(|ensnared| ensnared.entity == prey)
+            })
+        {
+            continue;
+        }
+
+        let first_particle_position = web.particles[spring.first_index].position;
+        let second_particle_position = web.particles[spring.second_index].position;
+        let along = second_particle_position - first_particle_position;
+        let t = if along.length_squared() > 0.0 {
+            ((wasp_transform.translation - first_particle_position).dot(along)
+                / along.length_squared())
+            .clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let springs_cut = web.破壊する(
+            first_particle_position + along * t,
+            &insect_query,
+            &mut commands,
+        );
+        if springs_cut > 0 {
+            ev_cut.send(WebCutEvent {
+                springs_cut,
+                cause: WebCutCause::Predator,
+            });
+        }
+    }
+}
+
+/// A wasp the spider touches lets go of whatever it was after and flees, away from the spider.
+fn fight_off_wasps(
+    spider_query: Query<(Entity, &Transform), With<Spider>>,
+    mut wasp_query: Query<(&mut PredatorWasp, &Transform), Without<Spider>>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut ev_driven_off: EventWriter<WaspDrivenOffEvent>,
+) {
+    let Ok((spider_entity, spider_transform)) = spider_query.get_single() else {
+        return;
+    };
+
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(entity_a, entity_b, _) = collision_event else {
+            continue;
+        };
+        let wasp_entity = if *entity_a == spider_entity {
+            *entity_b
+        } else if *entity_b == spider_entity {
+            *entity_a
+        } else {
+            continue;
+        };
+        let Ok((mut wasp, wasp_transform)) = wasp_query.get_mut(wasp_entity) else {
+            continue;
+        };
+        if wasp.driven_off {
+            continue;
+        }
+
+        info!("The spider drove a wasp off");
+        let away = (wasp_transform.translation - spider_transform.translation).normalize_or_zero();
+        wasp.driven_off = true;
+        wasp.state = WaspState::Leaving;
+        wasp.prey = None;
+        wasp.exit = wasp_transform.translation + away * DRIVEN_OFF_DISTANCE;
+        wasp.velocity = away * WASP_SPEED * DRIVEN_OFF_SPEED;
+        ev_driven_off.send(WaspDrivenOffEvent);
+    }
+}
+
+/// despawns every wasp
+fn clear_wasps(
+    mut commands: Commands,
+    mut ev_restarted: EventReader<GameRestarted>,
+    wasp_query: Query<Entity, With<PredatorWasp>>,
+) {
+    if ev_restarted.read().count() == 0 {
+        return;
+    }
+
+    for entity in &wasp_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
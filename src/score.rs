@@ -0,0 +1,272 @@
+use crate::config::GameConfig;
+use crate::game::{GameRestarted, GameState};
+use crate::health::IsDead;
+use crate::predator::{PreyStolenEvent, WaspDrivenOffEvent};
+use crate::spider::{Spider, SpiderFeastEvent};
+use crate::tree::GameStart;
+use crate::web::ensnare::InsectEnsnaredEvent;
+use crate::web::{WebCutCause, WebCutEvent};
+use bevy::prelude::*;
+
+pub struct ScorePlugin;
+
+/// What happened during the current run, reset whenever a new game starts
+#[derive(Resource, Default, Clone, Debug)]
+pub struct RunStats {
+    /// points from eating and catching insects, with the combo already applied
+    pub event_points: f32,
+    /// points from staying alive
+    pub survival_points: f32,
+    /// seconds since `GameStart`, paused time does not count
+    pub survival_time: f32,
+    pub insects_eaten: u32,
+    pub insects_ensnared: u32,
+    pub springs_cut_by_spider: u32,
+    /// strands cut by rocks and wasps
+    pub springs_cut_by_obstacles: u32,
+    /// caught insects carried off by wasps
+    pub insects_stolen: u32,
+    pub wasps_driven_off: u32,
+    /// total rest length of the strands the spider spun
+    pub silk_used: f32,
+    /// insects eaten in a row, each within `combo_window` of the last
+    pub combo: u32,
+    pub best_combo: u32,
+    /// survival time at which the current combo runs out
+    pub combo_expires_at: f32,
+}
+
+impl RunStats {
+    pub fn score(&self) -> u32 {
+        (self.event_points + self.survival_points) as u32
+    }
+
+    /// multiplier for the next insect eaten if it continues the current combo
+    pub fn combo_multiplier(&self, config: &GameConfig) -> f32 {
+        (1.0 + self.combo as f32 * config.combo_step).min(config.max_combo_multiplier)
+    }
+}
+
+/// the score shown while playing
+#[derive(Component)]
+struct ScoreText;
+
+/// the statistics shown after a game over
+#[derive(Component)]
+struct RunSummary;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>();
+        app.add_systems(Startup, spawn_score_text);
+        app.add_systems(
+            Update,
+            (
+                track_survival_time,
+                score_feasts.after(track_survival_time),
+                score_ensnares,
+                count_web_cuts,
+                count_wasps,
+                track_silk_used,
+            )
+                .run_if(in_state(GameState::Game)),
+        );
+        app.add_systems(Update, update_score_text);
+        app.add_systems(Update, reset_stats);
+        app.add_systems(
+            Update,
+            show_run_summary.run_if(in_state(GameState::GameOver)),
+        );
+        app.add_systems(OnExit(GameState::GameOver), hide_run_summary);
+    }
+}
+
+fn reset_stats(mut ev_restarted: EventReader<GameRestarted>, mut stats: ResMut<RunStats>) {
+    if ev_restarted.read().count() > 0 {
+        *stats = Default::default();
+    }
+}
+
+fn track_survival_time(
+    mut stats: ResMut<RunStats>,
+    start_query: Query<&GameStart>,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+) {
+    let Ok(start) = start_query.get_single() else {
+        return;
+    };
+
+    stats.survival_time = time.elapsed_seconds() - start.game_start;
+    stats.survival_points = stats.survival_time * config.survival_points_per_second;
+    if stats.combo > 0 && stats.survival_time > stats.combo_expires_at {
+        stats.combo = 0;
+    }
+}
+
+fn score_feasts(
+    mut ev_feast: EventReader<SpiderFeastEvent>,
+    mut stats: ResMut<RunStats>,
+    config: Res<GameConfig>,
+) {
+    for _ in ev_feast.read() {
+        let multiplier = stats.combo_multiplier(&config);
+        stats.event_points += config.feast_points * multiplier;
+        stats.insects_eaten += 1;
+        stats.combo += 1;
+        stats.best_combo = stats.best_combo.max(stats.combo);
+        stats.combo_expires_at = stats.survival_time + config.combo_window;
+    }
+}
+
+fn score_ensnares(
+    mut ev_ensnared: EventReader<InsectEnsnaredEvent>,
+    mut stats: ResMut<RunStats>,
+    config: Res<GameConfig>,
+) {
+    // an insect hitting two strands in the same frame gets caught twice
+    let mut caught: Vec<Entity> = ev_ensnared.read().map(|ev| ev.0).collect();
+    caught.sort();
+    caught.dedup();
+
+    stats.event_points += config.ensnare_points * caught.len() as f32;
+    stats.insects_ensnared += caught.len() as u32;
+}
+
+fn count_web_cuts(mut ev_cut: EventReader<WebCutEvent>, mut stats: ResMut<RunStats>) {
+    for cut in ev_cut.read() {
+        match cut.cause {
+            WebCutCause::Spider => stats.springs_cut_by_spider += cut.springs_cut as u32,
+            WebCutCause::Obstacle | WebCutCause::Predator => {
+                stats.springs_cut_by_obstacles += cut.springs_cut as u32
+            }
+        }
+    }
+}
+
+fn count_wasps(
+    mut ev_stolen: EventReader<PreyStolenEvent>,
+    mut ev_driven_off: EventReader<WaspDrivenOffEvent>,
+    mut stats: ResMut<RunStats>,
+) {
+    stats.insects_stolen += ev_stolen.read().count() as u32;
+    stats.wasps_driven_off += ev_driven_off.read().count() as u32;
+}
+
+fn track_silk_used(mut stats: ResMut<RunStats>, spider_query: Query<&Spider>) {
+    if let Ok(spider) = spider_query.get_single() {
+        stats.silk_used = spider.silk_used;
+    }
+}
+
+fn spawn_score_text(mut commands: Commands) {
+    commands.spawn((
+        ScoreText,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 24.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            top: Val::Px(20.0),
+            ..default()
+        }),
+    ));
+}
+
+fn update_score_text(
+    stats: Res<RunStats>,
+    state: Res<State<GameState>>,
+    config: Res<GameConfig>,
+    mut text_query: Query<&mut Text, With<ScoreText>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match state.get() {
+        GameState::Game | GameState::Paused if stats.combo > 0 => format!(
+            "Score {}\nCombo x{}",
+            stats.score(),
+            stats.combo_multiplier(&config)
+        ),
+        GameState::Game | GameState::Paused => format!("Score {}", stats.score()),
+        _ => String::new(),
+    };
+}
+
+/// waits for `update_death_screen` to finish moving the camera before showing the summary
+fn show_run_summary(
+    mut commands: Commands,
+    is_dead: Res<IsDead>,
+    stats: Res<RunStats>,
+    summary_query: Query<(), With<RunSummary>>,
+) {
+    if is_dead.death_camera_progress < 1.0 || !summary_query.is_empty() {
+        return;
+    }
+
+    let lines = [
+        format!("Score: {}", stats.score()),
+        format!("Survived: {:.1}s", stats.survival_time),
+        format!("Insects eaten: {}", stats.insects_eaten),
+        format!("Insects caught: {}", stats.insects_ensnared),
+        format!("Best combo: {}", stats.best_combo),
+        format!("Silk spun: {:.1}", stats.silk_used),
+        format!("Strands cut: {}", stats.springs_cut_by_spider),
+        format!("Strands destroyed: {}", stats.springs_cut_by_obstacles),
+        format!("Insects stolen: {}", stats.insects_stolen),
+        format!("Wasps driven off: {}", stats.wasps_driven_off),
+    ];
+
+    commands
+        .spawn((
+            RunSummary,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(62.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(12.0)),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.5).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for line in lines {
+                        parent.spawn(TextBundle::from_section(
+                            line,
+                            TextStyle {
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ));
+                    }
+                });
+        });
+}
+
+fn hide_run_summary(mut commands: Commands, summary_query: Query<Entity, With<RunSummary>>) {
+    for entity in &summary_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,12 +1,16 @@
-use crate::config::{COLLISION_GROUP_ALL, COLLISION_GROUP_PLAYER, COLLISION_GROUP_TERRAIN};
-use crate::flying_insect::flying_insect::{BezierCurve, FlyingInsect};
-use crate::game::GameState;
+use crate::config::{
+    GameConfig, COLLISION_GROUP_ALL, COLLISION_GROUP_PLAYER, COLLISION_GROUP_TERRAIN,
+};
+use crate::controls::{ControlAction, ControlState};
+use crate::flying_insect::flying_insect::FlyingInsect;
+use crate::game::{GameRestarted, GameState};
 use crate::health::IsDead;
 use crate::tree::{树里有小路吗, 树里有点吗, 照相机里有点吗};
 use crate::ui::progress_bar::CookingInsect;
 use crate::web::ensnare::{free_enemy_from_web, Ensnared};
+use crate::web::path::{PathLeg, Steer, WebLocation};
 use crate::web::spring::Spring;
-use crate::web::{Particle, Web};
+use crate::web::{Particle, ParticleKey, SpringKey, Web, WebCutCause, WebCutEvent, WebSimulation};
 use bevy::ecs::observer::TriggerTargets;
 use bevy::ecs::query::QueryEntityError;
 use bevy::input::touch::TouchPhase;
@@ -24,7 +28,9 @@ use std::f32::consts::PI;
 use std::time::Duration;
 
 pub const NNN: bool = false; // currently october, set this to true in november
-pub const SPIDER_ROTATE_SPEED: f32 = 5.6;
+pub const SPIDER_START_POSITION: Vec3 = Vec3::new(-2.0, -0.3, 0.0);
+/// how much shorter a new strand is made than the gap it spans, so it pulls tight
+const SILK_SLACK: f32 = 0.75;
 
 pub struct SpiderPlugin;
 
@@ -41,6 +47,9 @@ pub struct SpiderFeastEvent(pub f32);
 pub struct Spider {
     pub food: f32,
     pub max_food: f32,
+    /// silk left for spinning new strands, spent by their rest length
+    pub silk: f32,
+    pub max_silk: f32,
     pub current_position: SpiderPosition,
     pub current_rotation: f32,
     pub target_position: SpiderPosition,
@@ -48,11 +57,15 @@ pub struct Spider {
 
     pub current_roll: f32,
     pub lerp_roll: f32,
+    /// total rest length of the strands spun this game
+    pub silk_used: f32,
+    /// legs still to walk after reaching `target_position`, when following existing strands
+    pub route: Vec<PathLeg>,
 }
 
 #[derive(Copy, Clone)]
 enum SpiderPosition {
-    WEB(usize, f32),
+    WEB(SpringKey, f32),
     TREE(Vec3),
 }
 
@@ -94,6 +107,14 @@ impl SpiderPosition {
         }
     }
 
+    /// false when the spring under this position was cut or removed
+    fn is_on_web(&self, 网: &Web) -> bool {
+        match self {
+            SpiderPosition::WEB(第, _) => 网.springs.contains_key(*第),
+            SpiderPosition::TREE(_) => true,
+        }
+    }
+
     pub fn 同(&self, 其他: &SpiderPosition) -> bool {
         match (self, 其他) {
             (SpiderPosition::WEB(第1, t1), SpiderPosition::WEB(第2, t2)) => {
@@ -108,28 +129,109 @@ impl SpiderPosition {
 }
 
 impl Spider {
-    pub fn new(max_food: f32, pos: Vec3) -> Self {
+    pub fn new(max_food: f32, max_silk: f32, pos: Vec3) -> Self {
         Spider {
             food: max_food,
             max_food,
+            silk: max_silk,
+            max_silk,
             target_position: SpiderPosition::TREE(pos),
             current_position: SpiderPosition::TREE(pos),
             current_rotation: 0.0,
             touching_insects: vec![],
             current_roll: 0.0,
             lerp_roll: 0.0,
+            silk_used: 0.0,
+            route: vec![],
         }
     }
+
+    /// direction the spider is looking in the web plane, matches how `rotate_spider` turns it
+    pub fn facing(&self) -> Vec3 {
+        let θ = self.current_rotation + PI / 2.0;
+        Vec3::new(θ.cos(), θ.sin(), 0.0)
+    }
+
+    /// back on the tree where a new spider starts
+    pub fn return_to_start(&mut self) {
+        self.current_position = SpiderPosition::TREE(SPIDER_START_POSITION);
+        self.target_position = SpiderPosition::TREE(SPIDER_START_POSITION);
+        self.route.clear();
+    }
+
+    /// Follows the springs split or cut since the last call onto their new halves. Sends the
+    /// spider back to the tree if the spring it was standing on is gone anyway, and stops it if
+    /// the spring it was walking to, or any spring further along its route, is gone.
+    pub fn leave_removed_springs(&mut self, web: &mut Web) {
+        if !web.splits.is_empty() {
+            self.follow_splits(web);
+            web.splits.clear();
+        }
+
+        if !self.current_position.is_on_web(web) {
+            self.return_to_start();
+        } else if !self.target_position.is_on_web(web) {
+            self.target_position = self.current_position;
+            self.route.clear();
+        } else if self
+            .route
+            .iter()
+            .any(|leg| !web.springs.contains_key(leg.spring))
+        {
+            self.route.clear();
+        }
+    }
+
+    /// Moves the spider, the strand it is walking along and its route onto the halves of the
+    /// springs that were split
+    fn follow_splits(&mut self, web: &Web) {
+        let SpiderPosition::WEB(spring, from_t) = self.current_position else {
+            self.route = web.follow_splits(&self.route);
+            return;
+        };
+        let walking_to = match self.target_position {
+            SpiderPosition::WEB(target_spring, to_t) if target_spring == spring => Some(to_t),
+            _ => None,
+        };
+
+        let mut legs = vec![PathLeg {
+            spring,
+            from_t,
+            to_t: walking_to.unwrap_or(from_t),
+        }];
+        legs.extend_from_slice(&self.route);
+        let mut legs = web.follow_splits(&legs).into_iter();
+        if let Some(walk) = legs.next() {
+            self.current_position = SpiderPosition::WEB(walk.spring, walk.from_t);
+            if walking_to.is_some() {
+                self.target_position = SpiderPosition::WEB(walk.spring, walk.to_t);
+            }
+        }
+        // the rest of the walk, if it went across a split point, comes first
+        self.route = legs.collect();
+    }
 }
 
 impl Plugin for SpiderPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, spawn_spider);
+        app.add_systems(Update, respawn_spider);
         app.add_systems(Update, update_spider.run_if(in_state(GameState::Game)));
+        app.add_systems(
+            Update,
+            steer_spider
+                .before(update_spider)
+                .run_if(in_state(GameState::Game)),
+        );
         app.add_systems(
             Update,
             handle_ensnared_insect_collision.run_if(in_state(GameState::Game)),
         );
+        app.add_systems(Update, regenerate_silk.run_if(in_state(GameState::Game)));
+        app.add_systems(
+            Update,
+            apply_spider_limits.run_if(resource_changed::<GameConfig>),
+        );
         app.insert_resource(WebPlane {
             plane: Vec4::new(0.0, 0.0, -1.0, 0.0),
             left: Vec3::new(0.0, 1.0, 0.0),
@@ -143,13 +245,15 @@ fn update_spider(
     mut flies: Query<(&FlyingInsect, &Transform), With<Ensnared>>,
     q_windows: Query<&Window, With<PrimaryWindow>>,
     camera_query: Query<(&Camera, &GlobalTransform)>,
-    buttons: Res<ButtonInput<MouseButton>>,
+    controls: Res<ControlState>,
     touches: Res<Touches>,
     time: Res<Time>,
-    mut web_query: Query<&mut Web>,
+    mut web_query: Query<&mut WebSimulation>,
     mut is_dead: ResMut<IsDead>,
     spider_plane: Res<WebPlane>,
     rapier_context: Res<RapierContext>,
+    config: Res<GameConfig>,
+    mut ev_cut: EventWriter<WebCutEvent>,
 ) {
     let result = spider_query.get_single_mut();
 
@@ -159,11 +263,12 @@ fn update_spider(
     }
     let (mut spider, mut spider_transform) = result.unwrap();
 
-    spider.food -= 0.25 * time.delta_seconds();
+    spider.food -= config.food_drain * time.delta_seconds();
     if spider.food <= 0.0 {
         is_dead.is_dead = true;
     }
     let web = &mut *web_query.single_mut();
+    spider.leave_removed_springs(web);
     /*// tree position debug code
     if let Some(position) = q_windows.single().cursor_position() {
         let (camera, camera_global_transform) = camera_query.single();
@@ -180,7 +285,7 @@ fn update_spider(
             }
         }
     }*/
-    if buttons.just_pressed(MouseButton::Left) || touches.any_just_pressed() {
+    if controls.just_pressed(ControlAction::MoveTo) || touches.any_just_pressed() {
         let touch = touches.iter_just_pressed().next();
         let mut touch_pos = None;
         if touch.is_some() {
@@ -196,17 +301,24 @@ fn update_spider(
                 let λ = -(n.dot(ray.origin) + d) / (n.dot(*ray.direction));
                 let p = ray.origin + ray.direction * λ;
 
-                set_new_target(
-                    p - spider.current_position.to_vec3(web),
-                    &mut *spider,
-                    web,
-                    &rapier_context,
-                    camera,
-                    camera_global_transform,
-                );
+                if let Some(route) = route_over_web(p, &spider, web) {
+                    // move_spider starts on the first leg once it sees the spider has arrived
+                    spider.target_position = spider.current_position;
+                    spider.route = route;
+                } else {
+                    set_new_target(
+                        p - spider.current_position.to_vec3(web),
+                        &mut *spider,
+                        web,
+                        &rapier_context,
+                        camera,
+                        camera_global_transform,
+                        &config,
+                    );
+                }
             }
         }
-    } else if buttons.just_pressed(MouseButton::Right) {
+    } else if controls.just_pressed(ControlAction::Cut) {
         if let Some(position) = q_windows.single().cursor_position() {
             let (camera, camera_global_transform) = camera_query.single();
 
@@ -216,18 +328,23 @@ fn update_spider(
                 let λ = -(n.dot(ray.origin) + d) / (n.dot(*ray.direction));
                 let p = ray.origin + ray.direction * λ;
 
-                web.破壊する(p, &insect_query, &mut commands);
+                let springs_cut = web.破壊する(p, &insect_query, &mut commands);
+                if springs_cut > 0 {
+                    ev_cut.send(WebCutEvent {
+                        springs_cut,
+                        cause: WebCutCause::Spider,
+                    });
+                }
             }
         }
     }
 
     if !照相机里有点吗(spider_transform.translation) {
-        spider.current_position = SpiderPosition::TREE(Vec3::new(-2.0, -0.3, 0.0));
-        spider.target_position = SpiderPosition::TREE(Vec3::new(-2.0, -0.3, 0.0));
+        spider.return_to_start();
     }
 
     move_spider(web, &mut *spider, &time);
-    rotate_spider(web, &mut *spider, &time);
+    rotate_spider(web, &mut *spider, &time, &config);
 
     if spider.current_position.is_tree() {
         spider.current_roll = 0.0;
@@ -268,9 +385,254 @@ fn update_spider(
             * Quat::from_axis_angle(Vec3::new(1f32, 0f32, 0f32), spider.lerp_roll);
 }
 
+/// how close a click has to be to a trapped insect to walk over the web to it
+const INSECT_CLICK_RADIUS: f32 = 0.08;
+/// how close a click has to be to a strand to walk over the web to it
+const STRAND_CLICK_RADIUS: f32 = 0.03;
+/// walking over the web to a clicked strand is only done if the walk is at most this many times
+/// longer than spinning straight there
+const MAX_WALK_DETOUR: f32 = 2.0;
+
+/// The spider's position as a point on the web, stepping onto a strand tied to the tree if it
+/// is standing right at its end.
+fn web_location(position: SpiderPosition, web: &Web) -> Option<WebLocation> {
+    match position {
+        SpiderPosition::WEB(spring, t) => Some(WebLocation { spring, t }),
+        SpiderPosition::TREE(p) => web.springs.iter().find_map(|(key, spring)| {
+            if web.particles[spring.first_index]
+                .position
+                .distance_squared(p)
+                < 0.03 * 0.03
+            {
+                Some(WebLocation {
+                    spring: key,
+                    t: 0.0,
+                })
+            } else if web.particles[spring.second_index]
+                .position
+                .distance_squared(p)
+                < 0.03 * 0.03
+            {
+                Some(WebLocation {
+                    spring: key,
+                    t: 1.0,
+                })
+            } else {
+                None
+            }
+        }),
+    }
+}
+
+/// Route over existing strands to the trapped insect clicked on, or to the strand clicked on if
+/// walking there isn't much of a detour. `None` means the spider should spin silk there instead.
+fn route_over_web(point: Vec3, spider: &Spider, web: &Web) -> Option<Vec<PathLeg>> {
+    let start = web_location(spider.current_position, web)?;
+
+    let insect = web
+        .springs
+        .iter()
+        .flat_map(|(key, spring)| {
+            spring
+                .ensnared_entities
+                .iter()
+                .map(move |ensnared| WebLocation {
+                    spring: key,
+                    t: ensnared.snare_position,
+                })
+        })
+        .map(|location| (location, web.location_position(location).distance(point)))
+        .filter(|(_, distance)| *distance < INSECT_CLICK_RADIUS)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    if let Some((destination, _)) = insect {
+        return web.find_path(start, destination);
+    }
+
+    let destination = web.closest_location(point, STRAND_CLICK_RADIUS)?;
+    let route = web.find_path(start, destination)?;
+    let straight = spider
+        .current_position
+        .to_vec3(web)
+        .distance(web.location_position(destination));
+    if web.path_length(&route) <= straight * MAX_WALK_DETOUR {
+        Some(route)
+    } else {
+        None
+    }
+}
+
+/// how far ahead the spider looks for a branch to spin silk to when aiming with keys or a stick
+const SILK_AIM_REACH: f32 = 3.0;
+/// how far ahead of the spider the cut control cuts
+const CUT_REACH: f32 = 0.1;
+/// how far ahead the spider walks each frame while moving on the tree
+const TREE_STEP: f32 = 0.1;
+/// a strand must point at least this much in the move direction to be followed
+const STEER_MIN_ALIGNMENT: f32 = 0.3;
+
+/// Drives the spider from the keyboard or a gamepad, pointing at the web with the mouse or touch
+/// is handled in `update_spider`
+#[allow(clippy::too_many_arguments)]
+fn steer_spider(
+    mut commands: Commands,
+    mut spider_query: Query<&mut Spider, Without<Ensnared>>,
+    insect_query: Query<&FlyingInsect>,
+    mut web_query: Query<&mut WebSimulation>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    rapier_context: Res<RapierContext>,
+    controls: Res<ControlState>,
+    config: Res<GameConfig>,
+    mut ev_cut: EventWriter<WebCutEvent>,
+    mut steering: Local<bool>,
+) {
+    let Ok(mut spider) = spider_query.get_single_mut() else {
+        return;
+    };
+    let Ok((camera, camera_global_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let web = &mut *web_query.single_mut();
+    spider.leave_removed_springs(web);
+
+    let movement = Vec3::new(controls.movement.x, controls.movement.y, 0.0);
+    if movement != Vec3::ZERO {
+        steer_spider_towards(
+            movement.normalize(),
+            &mut spider,
+            web,
+            &rapier_context,
+            camera,
+            camera_global_transform,
+        );
+        *steering = true;
+    } else if *steering {
+        // stop where the spider is once the stick or keys are let go
+        spider.target_position = spider.current_position;
+        *steering = false;
+    }
+
+    let aim = if movement != Vec3::ZERO {
+        movement.normalize()
+    } else {
+        spider.facing()
+    };
+    let position = spider.current_position.to_vec3(web);
+
+    if controls.just_pressed(ControlAction::LaySilk) {
+        let target_δ = aim_silk(
+            position,
+            aim,
+            &rapier_context,
+            camera,
+            camera_global_transform,
+        );
+        set_new_target(
+            target_δ,
+            &mut spider,
+            web,
+            &rapier_context,
+            camera,
+            camera_global_transform,
+            &config,
+        );
+        *steering = false;
+    }
+
+    if controls.just_pressed(ControlAction::CutAhead) {
+        let p = position + aim * CUT_REACH;
+        let springs_cut = web.破壊する(p, &insect_query, &mut commands);
+        if springs_cut > 0 {
+            ev_cut.send(WebCutEvent {
+                springs_cut,
+                cause: WebCutCause::Spider,
+            });
+        }
+    }
+}
+
+/// Moves the spider along the strand it is on, onto the strand at a junction that best matches
+/// the direction, or across the tree.
+fn steer_spider_towards(
+    direction: Vec3,
+    spider: &mut Spider,
+    web: &Web,
+    rapier_context: &Res<RapierContext>,
+    cam: &Camera,
+    cam_transform: &GlobalTransform,
+) {
+    spider.route.clear();
+    let position = spider.current_position.to_vec3(web);
+
+    let SpiderPosition::WEB(spring_index, t) = spider.current_position else {
+        let next = position + direction * TREE_STEP;
+        if 树里有小路吗(position, next, rapier_context, cam, cam_transform) {
+            spider.target_position = SpiderPosition::TREE(next);
+        } else {
+            spider.target_position = spider.current_position;
+        }
+        return;
+    };
+
+    let heading_t = match spider.target_position {
+        SpiderPosition::WEB(target_spring, target_t) if target_spring == spring_index => target_t,
+        _ => t,
+    };
+    let from = WebLocation {
+        spring: spring_index,
+        t,
+    };
+    match web.steer(from, heading_t, direction, STEER_MIN_ALIGNMENT) {
+        Steer::Walk { from, to_t } => {
+            // only re-seat the spider when it turns onto another strand at a junction
+            if from.spring != spring_index {
+                spider.current_position = SpiderPosition::WEB(from.spring, from.t);
+            }
+            spider.target_position = SpiderPosition::WEB(from.spring, to_t);
+        }
+        Steer::Stuck {
+            junction: Some(junction),
+        } => {
+            // step off onto the tree if the strand is tied to it
+            let junction_position = web.particles[junction].position;
+            let next = junction_position + direction * TREE_STEP;
+            if web.particles[junction].pinned
+                && 树里有点吗(next, rapier_context, cam, cam_transform)
+            {
+                spider.current_position = SpiderPosition::TREE(junction_position);
+                spider.target_position = SpiderPosition::TREE(next);
+            } else {
+                spider.target_position = spider.current_position;
+            }
+        }
+        Steer::Stuck { junction: None } => spider.target_position = spider.current_position,
+    }
+}
+
+/// offset from the spider to the first branch in the aimed direction, or as far as it can
+/// reach if there is none
+fn aim_silk(
+    position: Vec3,
+    aim: Vec3,
+    rapier_context: &Res<RapierContext>,
+    cam: &Camera,
+    cam_transform: &GlobalTransform,
+) -> Vec3 {
+    let steps = (SILK_AIM_REACH / 0.05) as usize;
+    let mut left_tree = !树里有点吗(position, rapier_context, cam, cam_transform);
+    for i in 1..=steps {
+        let δ = aim * (i as f32 * 0.05);
+        if !树里有点吗(position + δ, rapier_context, cam, cam_transform) {
+            left_tree = true;
+        } else if left_tree {
+            return δ;
+        }
+    }
+    aim * SILK_AIM_REACH
+}
+
 fn handle_ensnared_insect_collision(
     mut commands: Commands,
-    mut web_query: Query<&mut Web>,
+    mut web_query: Query<&mut WebSimulation>,
     mut spider_query: Query<(&mut Spider, Entity)>,
     mut insects_query: Query<&mut FlyingInsect>,
     mut collision_events: EventReader<CollisionEvent>,
@@ -289,7 +651,7 @@ fn handle_ensnared_insect_collision(
     let mut on_touch_insect = |mut commands: &mut Commands,
                                mut insects_query: &mut Query<&mut FlyingInsect>,
                                insect_entity: Entity,
-                               mut web_query: &mut Query<&mut Web>,
+                               mut web_query: &mut Query<&mut WebSimulation>,
                                mut s: &mut Spider| {
         let Ok(mut insect) = insects_query.get_mut(insect_entity) else {
             error!("구르기 시작하거나 먹는 곤충이 발견되지 않음");
@@ -306,7 +668,7 @@ fn handle_ensnared_insect_collision(
     let mut on_leave_insect = |mut commands: &mut Commands,
                                mut insects_query: &mut Query<&mut FlyingInsect>,
                                insect_entity: Entity,
-                               mut web_query: &mut Query<&mut Web>,
+                               mut web_query: &mut Query<&mut WebSimulation>,
                                mut s: &mut Spider| {
         let Ok(mut insect) = insects_query.get_mut(insect_entity) else {
             error!("구르기 시작하거나 먹는 곤충이 발견되지 않음");
@@ -425,18 +787,19 @@ fn handle_ensnared_insect_collision(
 
         if insect.cooking_progress >= 1.0 {
             // TIME TO EAT!!!!!!
-            insect.snare_roll_progress = 0.0; // TODO: why do we need this?
-            ev_feast.send(SpiderFeastEvent(1.75));
+            ev_feast.send(SpiderFeastEvent(insect.food));
             commands
                 .entity(insect.rolled_ensnare_entity.unwrap())
                 .despawn();
 
+            // still wrapped up, so it doesn't kick the web on its way out
             free_enemy_from_web(
                 &mut commands,
                 *snaring_insect_entity,
                 Some(&insect),
                 &mut *web_query.single_mut(),
             );
+            insect.snare_roll_progress = 0.0; // TODO: why do we need this?
             commands
                 .entity(*snaring_insect_entity)
                 .remove::<BarSettings<CookingInsect>>();
@@ -468,7 +831,7 @@ fn handle_ensnared_insect_collision(
                 .insert(CookingInsect { progress: 0.0 });
 
             let mut web = web_query.single_mut();
-            for spring in &mut web.springs {
+            for spring in web.springs.values_mut() {
                 for ensnared in &mut spring.ensnared_entities {
                     if ensnared.entity == *snaring_insect_entity {
                         ensnared.done_ensnaring = true;
@@ -482,7 +845,15 @@ fn handle_ensnared_insect_collision(
 
 fn move_spider(web: &Web, spider: &mut Spider, time: &Res<Time>) {
     if spider.current_position.同(&spider.target_position) {
-        return; // spider not moving
+        if spider.route.is_empty() {
+            return; // spider not moving
+        }
+        let leg = spider.route.remove(0);
+        spider.current_position = SpiderPosition::WEB(leg.spring, leg.from_t);
+        spider.target_position = SpiderPosition::WEB(leg.spring, leg.to_t);
+        if spider.current_position.同(&spider.target_position) {
+            return;
+        }
     }
 
     let position = spider.current_position.to_vec3(web);
@@ -498,7 +869,7 @@ fn move_spider(web: &Web, spider: &mut Spider, time: &Res<Time>) {
     }
 }
 
-fn rotate_spider(web: &Web, spider: &mut Spider, time: &Res<Time>) {
+fn rotate_spider(web: &Web, spider: &mut Spider, time: &Res<Time>, config: &GameConfig) {
     let position = spider.current_position.to_vec3(web);
     let destination = spider.target_position.to_vec3(web);
     if (position - destination).length_squared() < 0.01 * 0.01 {
@@ -519,7 +890,7 @@ fn rotate_spider(web: &Web, spider: &mut Spider, time: &Res<Time>) {
         // move
     } else {
         // rotate
-        let angular_velocity = SPIDER_ROTATE_SPEED * PI * time.delta_seconds();
+        let angular_velocity = config.spider_rotate_speed * PI * time.delta_seconds();
         let 新θ = if (current_angle - θ).abs() < ((current_angle - θ).abs() - 2.0 * PI).abs() {
             let diff_sign = (current_angle - θ).signum();
             let updated_angle = current_angle + angular_velocity * (θ - current_angle).signum();
@@ -556,7 +927,9 @@ fn set_new_target(
     rapier_context: &Res<RapierContext>,
     cam: &Camera,
     cam_transform: &GlobalTransform,
+    config: &GameConfig,
 ) {
+    spider.route.clear();
     let position = spider.current_position.to_vec3(web);
 
     if target_δ.length_squared() < 0.01 {
@@ -572,13 +945,13 @@ fn set_new_target(
     let target_dir = target_δ.normalize();
     let mut target_pos = position + target_dir * 10.0;
 
-    let mut dest_spring_idx: Option<usize> = None;
-    let mut from_spring: Option<(usize, f32)> = match spider.current_position {
+    let mut dest_spring_idx: Option<SpringKey> = None;
+    let from_spring: Option<(SpringKey, f32)> = match spider.current_position {
         SpiderPosition::WEB(idx, t) => Some((idx, t)),
         SpiderPosition::TREE(_) => None,
     };
 
-    let mut from_particle_idx: Option<usize> = None;
+    let mut from_particle_idx: Option<ParticleKey> = None;
 
     if let Some((spring_index, _)) = from_spring {
         let from_spring: &Spring = &web.springs[spring_index];
@@ -606,9 +979,7 @@ fn set_new_target(
     }
 
     if from_particle_idx.is_some() {
-        for i in 0..web.springs.len() {
-            let spring: &Spring = &web.springs[i];
-
+        for (i, spring) in web.springs.iter() {
             if spring.first_index == from_particle_idx.unwrap()
                 || spring.second_index == from_particle_idx.unwrap()
             {
@@ -661,12 +1032,11 @@ fn set_new_target(
         }
     }
 
-    for i in 0..web.springs.len() {
+    for (i, spring) in web.springs.iter() {
         if from_spring.is_some() && from_spring.unwrap().0 == i {
             continue;
         }
 
-        let spring = &web.springs[i];
         let result = spring.intersects(
             web,
             Vec3::new(0.0, 0.0, -1.0),
@@ -772,13 +1142,16 @@ fn set_new_target(
         }
     }
 
-    let hack_spring_count = web.springs.len();
-    let mut hack_swap_removed_a_spring = false;
+    let start = existing_p1.map_or(position, |p| web.particles[p].position);
+    let end = existing_p2.map_or(target_pos, |p| web.particles[p].position);
+    if start.distance(end) * SILK_SLACK > spider.silk {
+        debug!("Not enough silk left to spin that far");
+        return;
+    }
 
     let p1 = if existing_p1.is_none() {
         if let Some((from_spring_index, _)) = from_spring {
-            web.split_spring(from_spring_index, position);
-            hack_swap_removed_a_spring = true;
+            web.split_spring(from_spring_index, position)
         } else {
             let in_tree = 树里有点吗(position, rapier_context, cam, cam_transform);
             //if !in_tree {
@@ -786,7 +1159,7 @@ fn set_new_target(
             //    return;
             //}
 
-            web.particles.push(Particle {
+            web.particles.insert(Particle {
                 position: position,
                 velocity: Default::default(),
                 force: Default::default(),
@@ -794,9 +1167,8 @@ fn set_new_target(
                 impulse_duration: 0.0,
                 mass: 0.0,
                 pinned: in_tree,
-            });
+            })
         }
-        web.particles.len() - 1
     } else {
         existing_p1.unwrap()
     };
@@ -809,7 +1181,7 @@ fn set_new_target(
                 return;
             }
 
-            web.particles.push(Particle {
+            web.particles.insert(Particle {
                 position: target_pos,
                 velocity: Default::default(),
                 force: Default::default(),
@@ -817,44 +1189,99 @@ fn set_new_target(
                 impulse_duration: 0.0,
                 mass: 0.0,
                 pinned: true,
-            });
+            })
         } else {
-            // 可怕的黑客
-            // 基本上这意味着 dest_spring_idx 在上述对 split_spring 的调用中由于其内部发生的 swap_remove
-            // 调用而失效，因此我们检测到此副作用并更正索引
-            if hack_swap_removed_a_spring && dest_spring_idx.unwrap() == hack_spring_count - 1 {
-                dest_spring_idx = Some(from_spring.unwrap().0);
-            }
-
-            web.split_spring(dest_spring_idx.unwrap(), target_pos);
+            web.split_spring(dest_spring_idx.unwrap(), target_pos)
         }
-        web.particles.len() - 1
     } else {
         existing_p2.unwrap()
     };
 
-    web.springs.push(Spring::new_with_length(
+    let silk_length =
+        (web.particles[p1].position - web.particles[p2].position).length() * SILK_SLACK;
+    let new_spring = web.springs.insert(Spring::new_with_length(
         web,
         p1,
         p2,
-        20.0,
-        0.5,
-        (web.particles[p1].position - web.particles[p2].position).length() * 0.75,
+        config.silk_stiffness,
+        config.silk_damping,
+        silk_length,
         vec![],
     ));
+    spider.silk_used += silk_length;
+    spider.silk = (spider.silk - silk_length).max(0.0);
 
-    spider.current_position = SpiderPosition::WEB(web.springs.len() - 1, 0.0);
-    spider.target_position = SpiderPosition::WEB(web.springs.len() - 1, 1.0);
+    spider.current_position = SpiderPosition::WEB(new_spring, 0.0);
+    spider.target_position = SpiderPosition::WEB(new_spring, 1.0);
     println!("New path created");
 }
 
+/// The spider slowly makes more silk, and a good meal gives it a lot more.
+fn regenerate_silk(
+    mut spider_query: Query<&mut Spider>,
+    mut ev_feast: EventReader<SpiderFeastEvent>,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+) {
+    let Ok(mut spider) = spider_query.get_single_mut() else {
+        return;
+    };
+    let feasts = ev_feast.read().count() as f32;
+    let silk = spider.silk + config.silk_regen * time.delta_seconds() + config.feast_silk * feasts;
+    spider.silk = silk.min(spider.max_silk);
+}
+
 fn spawn_spider(
     mut commands: Commands,
     asset_server: ResMut<AssetServer>,
     mut _camera_transform_query: Query<(&mut Transform, &Camera)>,
     spider_plane: Res<WebPlane>,
+    config: Res<GameConfig>,
+) {
+    spawn_spider_entity(&mut commands, &asset_server, &spider_plane, &config);
+}
+
+/// Keeps the spider's limits in step with the config, which loads after the first spider is
+/// spawned. Food and silk keep the same share of the new limits.
+fn apply_spider_limits(mut spider_query: Query<&mut Spider>, config: Res<GameConfig>) {
+    for mut spider in spider_query.iter_mut() {
+        if spider.max_food > 0.0 {
+            spider.food *= config.max_food / spider.max_food;
+        }
+        if spider.max_silk > 0.0 {
+            spider.silk *= config.max_silk / spider.max_silk;
+        }
+        spider.max_food = config.max_food;
+        spider.max_silk = config.max_silk;
+    }
+}
+
+/// a fresh, well fed spider for a new game
+fn respawn_spider(
+    mut commands: Commands,
+    mut ev_restarted: EventReader<GameRestarted>,
+    asset_server: Res<AssetServer>,
+    spider_query: Query<Entity, With<Spider>>,
+    spider_plane: Res<WebPlane>,
+    config: Res<GameConfig>,
+) {
+    if ev_restarted.read().count() == 0 {
+        return;
+    }
+
+    for entity in &spider_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    spawn_spider_entity(&mut commands, &asset_server, &spider_plane, &config);
+}
+
+fn spawn_spider_entity(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    spider_plane: &WebPlane,
+    config: &GameConfig,
 ) {
-    let start_pos = Vec3::new(-2.0, -0.3, 0.0);
+    let start_pos = SPIDER_START_POSITION;
     let spider_plane_up = spider_plane.plane.xyz().cross(spider_plane.left);
     let base_transform_mat = bevy::math::mat3(
         spider_plane.left,
@@ -863,7 +1290,7 @@ fn spawn_spider(
     );
     commands
         .spawn((
-            Spider::new(10.0, start_pos),
+            Spider::new(config.max_food, config.max_silk, start_pos),
             SceneBundle {
                 scene: asset_server.load("spider.glb#Scene0"),
                 transform: Transform {
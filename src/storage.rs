@@ -0,0 +1,74 @@
+/// Name of the folder in the platform data dir that the game's files are kept in.
+#[cfg(not(target_arch = "wasm32"))]
+const DATA_DIR_NAME: &str = "silk-and-snare";
+
+/// Why a file kept between runs could not be read or written
+#[derive(Debug)]
+pub enum StorageError {
+    Io(std::io::Error),
+    /// no data dir on desktop, or no `localStorage` in the browser
+    Unavailable(String),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::Io(err) => write!(f, "{}", err),
+            StorageError::Unavailable(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn storage_path(name: &str) -> Result<std::path::PathBuf, StorageError> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| StorageError::Unavailable("no data directory".to_string()))?;
+    Ok(data_dir.join(DATA_DIR_NAME).join(name))
+}
+
+/// Contents of a file in the platform data dir, `None` if it was never written.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_text(name: &str) -> Result<Option<String>, StorageError> {
+    let path = storage_path(name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    std::fs::read_to_string(path)
+        .map(Some)
+        .map_err(StorageError::Io)
+}
+
+/// Replaces a file in the platform data dir, creating the folder if needed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_text(name: &str, text: &str) -> Result<(), StorageError> {
+    let path = storage_path(name)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(StorageError::Io)?;
+    }
+    std::fs::write(path, text).map_err(StorageError::Io)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, StorageError> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| StorageError::Unavailable("localStorage is not available".to_string()))
+}
+
+/// The `localStorage` item with this name, `None` if it was never written.
+#[cfg(target_arch = "wasm32")]
+pub fn read_text(name: &str) -> Result<Option<String>, StorageError> {
+    local_storage()?
+        .get_item(name)
+        .map_err(|err| StorageError::Unavailable(format!("{:?}", err)))
+}
+
+/// Replaces the `localStorage` item with this name.
+#[cfg(target_arch = "wasm32")]
+pub fn write_text(name: &str, text: &str) -> Result<(), StorageError> {
+    local_storage()?
+        .set_item(name, text)
+        .map_err(|err| StorageError::Unavailable(format!("{:?}", err)))
+}
@@ -1,29 +1,259 @@
+use crate::controls::{ControlAction, ControlBindings, ControlState};
 use crate::game::GameState;
+use crate::high_score::HighScores;
 use crate::tree::GameStart;
+use crate::waves::Difficulty;
 use bevy::prelude::*;
+use ld56_sim::high_score::HighScoreEntry;
 
 pub struct TitleScreenPlugin;
 
+/// the hint at the bottom of the title screen
+#[derive(Component)]
+struct TitleScreenText;
+
+/// the line of the hint showing the difficulty
+#[derive(Component)]
+struct DifficultyText;
+
+/// root of the high score screen
+#[derive(Component)]
+struct HighScoreScreen;
+
 impl Plugin for TitleScreenPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            start_game_click_handler.run_if(in_state(GameState::TitleScreen)),
+            (start_game_click_handler, change_difficulty).run_if(in_state(GameState::TitleScreen)),
+        );
+        app.add_systems(
+            Update,
+            toggle_high_scores
+                .run_if(in_state(GameState::TitleScreen).or_else(in_state(GameState::HighScores))),
         );
+        app.add_systems(OnEnter(GameState::TitleScreen), show_title_screen_text);
+        app.add_systems(OnExit(GameState::TitleScreen), hide_title_screen_text);
+        app.add_systems(OnEnter(GameState::HighScores), show_high_scores);
+        app.add_systems(OnExit(GameState::HighScores), hide_high_scores);
     }
 }
 
 fn start_game_click_handler(
     mut next_state: ResMut<NextState<GameState>>,
-    buttons: Res<ButtonInput<MouseButton>>,
+    controls: Res<ControlState>,
     touches: Res<Touches>,
     time: Res<Time>,
     mut commands: Commands,
 ) {
-    if buttons.just_pressed(MouseButton::Left) || touches.any_just_pressed() {
+    if controls.just_pressed(ControlAction::MoveTo) || touches.any_just_pressed() {
         next_state.set(GameState::Game);
         commands.spawn(GameStart {
             game_start: time.elapsed_seconds(),
         });
     }
 }
+
+/// opens the high scores from the title screen, the same action, pause or a click goes back
+fn toggle_high_scores(
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    controls: Res<ControlState>,
+    touches: Res<Touches>,
+) {
+    match state.get() {
+        GameState::TitleScreen if controls.just_pressed(ControlAction::ShowHighScores) => {
+            next_state.set(GameState::HighScores);
+        }
+        GameState::HighScores
+            if controls.just_pressed(ControlAction::ShowHighScores)
+                || controls.just_pressed(ControlAction::Pause)
+                || controls.just_pressed(ControlAction::MoveTo)
+                || touches.any_just_pressed() =>
+        {
+            next_state.set(GameState::TitleScreen);
+        }
+        _ => {}
+    }
+}
+
+fn change_difficulty(
+    mut difficulty: ResMut<Difficulty>,
+    controls: Res<ControlState>,
+    bindings: Res<ControlBindings>,
+    mut text_query: Query<&mut Text, With<DifficultyText>>,
+) {
+    if controls.just_pressed(ControlAction::ChangeDifficulty) {
+        *difficulty = difficulty.next();
+        for mut text in &mut text_query {
+            text.sections[0].value = difficulty_hint(*difficulty, &bindings);
+        }
+    }
+}
+
+fn difficulty_hint(difficulty: Difficulty, bindings: &ControlBindings) -> String {
+    format!(
+        "Difficulty: {}, {} to change",
+        difficulty.label(),
+        bindings.label(ControlAction::ChangeDifficulty)
+    )
+}
+
+fn show_title_screen_text(
+    mut commands: Commands,
+    difficulty: Res<Difficulty>,
+    bindings: Res<ControlBindings>,
+) {
+    commands
+        .spawn((
+            TitleScreenText,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(60.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            let text_style = TextStyle {
+                font_size: 28.0,
+                color: Color::WHITE,
+                ..default()
+            };
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "{} to start, {} for high scores, {} for controls",
+                    bindings.label(ControlAction::MoveTo),
+                    bindings.label(ControlAction::ShowHighScores),
+                    bindings.label(ControlAction::ShowControls)
+                ),
+                text_style.clone(),
+            ));
+            parent.spawn((
+                DifficultyText,
+                TextBundle::from_section(difficulty_hint(*difficulty, &bindings), text_style),
+            ));
+        });
+}
+
+fn hide_title_screen_text(
+    mut commands: Commands,
+    text_query: Query<Entity, With<TitleScreenText>>,
+) {
+    for entity in &text_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn high_score_column(parent: &mut ChildBuilder, title: &str, entries: &[&HighScoreEntry]) {
+    let text_style = TextStyle {
+        font_size: 20.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::horizontal(Val::Px(20.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font_size: 28.0,
+                    ..text_style.clone()
+                },
+            ));
+
+            if entries.is_empty() {
+                parent.spawn(TextBundle::from_section("No runs yet", text_style.clone()));
+            }
+
+            for (rank, entry) in entries.iter().enumerate() {
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "{:>2}. {}  {:>6.1}s  {:>3} eaten  {:>5.1} silk  {} pts",
+                        rank + 1,
+                        entry.date_string(),
+                        entry.survival_time,
+                        entry.insects_eaten,
+                        entry.silk_used,
+                        entry.score
+                    ),
+                    text_style.clone(),
+                ));
+            }
+        });
+}
+
+fn show_high_scores(
+    mut commands: Commands,
+    table: Res<HighScores>,
+    bindings: Res<ControlBindings>,
+) {
+    commands
+        .spawn((
+            HighScoreScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    high_score_column(parent, "Longest survival", &table.by_survival_time());
+                    high_score_column(parent, "Most insects eaten", &table.by_insects_eaten());
+                });
+
+            parent.spawn(
+                TextBundle::from_section(
+                    format!(
+                        "{} or {} to go back",
+                        bindings.label(ControlAction::MoveTo),
+                        bindings.label(ControlAction::ShowHighScores)
+                    ),
+                    TextStyle {
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(30.0)),
+                    ..default()
+                }),
+            );
+        });
+}
+
+fn hide_high_scores(mut commands: Commands, screen_query: Query<Entity, With<HighScoreScreen>>) {
+    for entity in &screen_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
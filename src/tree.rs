@@ -1,7 +1,8 @@
 use std::f32::consts::PI;
 
 use crate::config::COLLISION_GROUP_TERRAIN;
-use crate::flying_insect::fruit_fly::DAVID_DEBUG;
+use crate::controls::{ControlAction, ControlState};
+use crate::flying_insect::spawn::DAVID_DEBUG;
 use crate::game::GameState;
 use crate::health::IsDead;
 use crate::{
@@ -68,7 +69,7 @@ fn move_to_tree(
     mut camera_transform_query: Query<(&mut Transform, &Camera)>,
     time: Res<Time>,
     mut swap_camera_angle: Local<bool>,
-    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<ControlState>,
     start_query: Query<&GameStart>,
     is_dead: Res<IsDead>,
 ) {
@@ -76,7 +77,7 @@ fn move_to_tree(
         let s = ((time.elapsed_seconds() - start.game_start) / 2.0).min(1.0);
         let t = 3.0 * s * s - 2.0 * s * s * s;
 
-        if keys.just_released(KeyCode::KeyQ) && DAVID_DEBUG {
+        if controls.just_pressed(ControlAction::SwapCamera) && DAVID_DEBUG {
             *swap_camera_angle = !*swap_camera_angle;
         }
 
@@ -0,0 +1,268 @@
+use crate::controls::{
+    save_control_bindings, ControlAction, ControlBinding, ControlBindings, ControlState,
+};
+use crate::game::GameState;
+use bevy::prelude::*;
+
+pub struct ControlsMenuPlugin;
+
+/// Which action is waiting for a button, and where to go back to once the menu closes
+#[derive(Resource)]
+struct ControlsMenu {
+    listening: Option<ControlAction>,
+    return_state: GameState,
+}
+
+/// root of the controls menu
+#[derive(Component)]
+struct ControlsMenuRoot;
+
+#[derive(Component, Clone, Copy)]
+enum ControlsMenuButton {
+    Action(ControlAction),
+    Reset,
+    Back,
+}
+
+/// text showing the buttons bound to an action
+#[derive(Component)]
+struct ActionBindingText(ControlAction);
+
+const BUTTON_COLOR: Color = Color::srgba(0.2, 0.2, 0.3, 0.8);
+const BUTTON_HOVER_COLOR: Color = Color::srgba(0.35, 0.35, 0.5, 0.9);
+
+impl Plugin for ControlsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ControlsMenu {
+            listening: None,
+            return_state: GameState::TitleScreen,
+        });
+        app.add_systems(
+            Update,
+            open_controls_menu
+                .run_if(in_state(GameState::TitleScreen).or_else(in_state(GameState::Paused))),
+        );
+        app.add_systems(
+            Update,
+            (
+                capture_binding,
+                handle_menu_buttons.after(capture_binding),
+                update_binding_text.after(handle_menu_buttons),
+            )
+                .run_if(in_state(GameState::ControlsMenu)),
+        );
+        app.add_systems(OnEnter(GameState::ControlsMenu), spawn_controls_menu);
+        app.add_systems(OnExit(GameState::ControlsMenu), despawn_controls_menu);
+    }
+}
+
+fn open_controls_menu(
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut menu: ResMut<ControlsMenu>,
+    controls: Res<ControlState>,
+) {
+    if controls.just_pressed(ControlAction::ShowControls) {
+        menu.listening = None;
+        menu.return_state = state.get().clone();
+        next_state.set(GameState::ControlsMenu);
+    }
+}
+
+fn save(bindings: &ControlBindings) {
+    if let Err(err) = save_control_bindings(bindings) {
+        error!("Could not save controls: {}", err);
+    }
+}
+
+/// While an action is waiting, the next key, mouse button or gamepad button pressed is bound to
+/// it. Esc cancels waiting, or closes the menu when nothing is waiting. Clicks on the menu's own
+/// buttons are left to `handle_menu_buttons`.
+fn capture_binding(
+    mut menu: ResMut<ControlsMenu>,
+    mut bindings: ResMut<ControlBindings>,
+    mut next_state: ResMut<NextState<GameState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    button_query: Query<&Interaction, With<ControlsMenuButton>>,
+) {
+    let Some(action) = menu.listening else {
+        if keys.just_pressed(KeyCode::Escape) {
+            next_state.set(menu.return_state.clone());
+        }
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        menu.listening = None;
+        return;
+    }
+
+    let over_menu_button = button_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+    let binding = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| ControlBinding::Key(*key))
+        .or_else(|| {
+            mouse_buttons
+                .get_just_pressed()
+                .find(|_| !over_menu_button)
+                .map(|button| ControlBinding::Mouse(*button))
+        })
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| ControlBinding::Gamepad(button.button_type))
+        });
+
+    if let Some(binding) = binding {
+        debug!("Binding {} to {:?}", binding.label(), action);
+        bindings.rebind(action, binding);
+        save(&bindings);
+        menu.listening = None;
+    }
+}
+
+fn handle_menu_buttons(
+    mut menu: ResMut<ControlsMenu>,
+    mut bindings: ResMut<ControlBindings>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut button_query: Query<
+        (&Interaction, &ControlsMenuButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, button, mut background_color) in &mut button_query {
+        match interaction {
+            Interaction::Pressed => match button {
+                ControlsMenuButton::Action(action) => menu.listening = Some(*action),
+                ControlsMenuButton::Reset => {
+                    *bindings = Default::default();
+                    save(&bindings);
+                    menu.listening = None;
+                }
+                ControlsMenuButton::Back => next_state.set(menu.return_state.clone()),
+            },
+            Interaction::Hovered => *background_color = BUTTON_HOVER_COLOR.into(),
+            Interaction::None => *background_color = BUTTON_COLOR.into(),
+        }
+    }
+}
+
+fn update_binding_text(
+    menu: Res<ControlsMenu>,
+    bindings: Res<ControlBindings>,
+    mut text_query: Query<(&mut Text, &ActionBindingText)>,
+) {
+    if !menu.is_changed() && !bindings.is_changed() {
+        return;
+    }
+
+    for (mut text, ActionBindingText(action)) in &mut text_query {
+        text.sections[0].value = if menu.listening == Some(*action) {
+            format!("{}: press a button...", action.label())
+        } else {
+            format!("{}: {}", action.label(), bindings.label(*action))
+        };
+    }
+}
+
+fn spawn_menu_button(parent: &mut ChildBuilder, button: ControlsMenuButton, text: &str) {
+    parent
+        .spawn((
+            button,
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(520.0),
+                    padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+                    margin: UiRect::vertical(Val::Px(2.0)),
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            let mut text = parent.spawn(TextBundle::from_section(
+                text,
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            if let ControlsMenuButton::Action(action) = button {
+                text.insert(ActionBindingText(action));
+            }
+        });
+}
+
+fn spawn_controls_menu(mut commands: Commands, bindings: Res<ControlBindings>) {
+    commands
+        .spawn((
+            ControlsMenuRoot,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Controls",
+                TextStyle {
+                    font_size: 36.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+
+            for action in ControlAction::ALL {
+                spawn_menu_button(
+                    parent,
+                    ControlsMenuButton::Action(action),
+                    &format!("{}: {}", action.label(), bindings.label(action)),
+                );
+            }
+
+            parent.spawn(
+                TextBundle::from_section(
+                    "Click an action, then press the key or gamepad button for it, \
+                     or click away from the buttons to bind a mouse button",
+                    TextStyle {
+                        font_size: 18.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::vertical(Val::Px(12.0)),
+                    ..default()
+                }),
+            );
+
+            spawn_menu_button(parent, ControlsMenuButton::Reset, "Reset to defaults");
+            spawn_menu_button(parent, ControlsMenuButton::Back, "Back");
+        });
+}
+
+fn despawn_controls_menu(
+    mut commands: Commands,
+    root_query: Query<Entity, With<ControlsMenuRoot>>,
+) {
+    for entity in &root_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
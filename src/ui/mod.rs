@@ -1 +1,2 @@
+pub mod controls_menu;
 pub mod progress_bar;
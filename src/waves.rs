@@ -0,0 +1,370 @@
+use crate::config::GameConfig;
+use crate::flying_insect::species::choose_species;
+use crate::flying_obstacle::flying_obstacle::ObstacleKind;
+use crate::game::{GameRestarted, GameState};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use serde::{Deserialize, Serialize};
+
+/// Path of the wave file, relative to the assets folder.
+pub const WAVES_PATH: &str = "game.waves.ron";
+
+/// spawns never come closer together than this, however hard the difficulty
+const MIN_SPAWN_INTERVAL: f32 = 0.2;
+
+pub struct WavesPlugin;
+
+/// How hard the waves are, picked on the title screen
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+/// How a difficulty changes the waves
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DifficultySettings {
+    /// multiplies the seconds between spawns, below 1 is harder
+    pub spawn_interval_scale: f32,
+    /// multiplies the rest between waves, below 1 is harder
+    pub rest_scale: f32,
+}
+
+/// One stretch of the game with its own mix of insects and obstacles
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Wave {
+    pub name: String,
+    /// seconds the wave lasts
+    pub duration: f32,
+    /// seconds between insects
+    pub insect_interval: f32,
+    /// species names and how often each comes, every species by its `spawn_weight` if empty
+    pub insects: Vec<(String, f32)>,
+    /// seconds between obstacles
+    pub obstacle_interval: f32,
+    /// obstacle kinds and how often each comes, none if empty
+    pub obstacles: Vec<(ObstacleKind, f32)>,
+    /// quiet seconds after the wave, before the next one starts
+    pub rest: f32,
+}
+
+/// The waves of a game, read from `assets/game.waves.ron`. Until the file is loaded, or if it is
+/// missing or broken, the defaults below are used.
+#[derive(Asset, Resource, TypePath, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Waves {
+    pub easy: DifficultySettings,
+    pub normal: DifficultySettings,
+    pub hard: DifficultySettings,
+    pub waves: Vec<Wave>,
+    /// once the last wave is over, the waves from this one on come again
+    pub repeat_from: usize,
+    /// multiplies the seconds between spawns every time the waves come again
+    pub repeat_interval_scale: f32,
+}
+
+impl Default for Waves {
+    fn default() -> Self {
+        let fruit_fly = || ("fruit fly".to_string(), 4.0);
+        let moth = || ("moth".to_string(), 2.0);
+        let beetle = || ("beetle".to_string(), 1.0);
+        let wasp = || ("wasp".to_string(), 1.0);
+        let gnats = || ("gnat".to_string(), 0.5);
+        Waves {
+            easy: DifficultySettings {
+                spawn_interval_scale: 1.4,
+                rest_scale: 1.5,
+            },
+            normal: DifficultySettings {
+                spawn_interval_scale: 1.0,
+                rest_scale: 1.0,
+            },
+            hard: DifficultySettings {
+                spawn_interval_scale: 0.75,
+                rest_scale: 0.6,
+            },
+            waves: vec![
+                Wave {
+                    name: "First flies".to_string(),
+                    duration: 40.0,
+                    insect_interval: 2.0,
+                    insects: vec![fruit_fly()],
+                    obstacle_interval: 15.0,
+                    obstacles: vec![],
+                    rest: 6.0,
+                },
+                Wave {
+                    name: "Moths come out".to_string(),
+                    duration: 45.0,
+                    insect_interval: 1.6,
+                    insects: vec![fruit_fly(), moth()],
+                    obstacle_interval: 15.0,
+                    obstacles: vec![(ObstacleKind::Rock, 1.0), (ObstacleKind::Leaf, 1.0)],
+                    rest: 6.0,
+                },
+                Wave {
+                    name: "Beetles".to_string(),
+                    duration: 50.0,
+                    insect_interval: 1.5,
+                    insects: vec![fruit_fly(), moth(), beetle(), gnats()],
+                    obstacle_interval: 9.0,
+                    obstacles: vec![
+                        (ObstacleKind::Rock, 1.0),
+                        (ObstacleKind::Leaf, 0.5),
+                        (ObstacleKind::Raindrops, 0.5),
+                        (ObstacleKind::PredatorWasp, 0.3),
+                    ],
+                    rest: 8.0,
+                },
+                Wave {
+                    name: "Everything at once".to_string(),
+                    duration: 60.0,
+                    insect_interval: 1.2,
+                    insects: vec![fruit_fly(), moth(), beetle(), wasp(), gnats()],
+                    obstacle_interval: 6.0,
+                    obstacles: vec![
+                        (ObstacleKind::Rock, 1.0),
+                        (ObstacleKind::Leaf, 0.5),
+                        (ObstacleKind::Raindrops, 0.5),
+                        (ObstacleKind::Bat, 0.4),
+                        (ObstacleKind::PredatorWasp, 0.5),
+                    ],
+                    rest: 8.0,
+                },
+            ],
+            repeat_from: 2,
+            repeat_interval_scale: 0.85,
+        }
+    }
+}
+
+impl Waves {
+    pub fn settings(&self, difficulty: Difficulty) -> &DifficultySettings {
+        match difficulty {
+            Difficulty::Easy => &self.easy,
+            Difficulty::Normal => &self.normal,
+            Difficulty::Hard => &self.hard,
+        }
+    }
+}
+
+/// Where the current game is in the waves
+#[derive(Resource, Default, Debug)]
+pub struct WaveDirector {
+    /// index into `Waves::waves`
+    pub wave: usize,
+    /// how many times the waves have come again
+    pub repeats: u32,
+    /// in the quiet after the current wave
+    pub resting: bool,
+    /// seconds into the current wave, or into the rest after it
+    pub elapsed: f32,
+    since_insect: f32,
+    since_obstacle: f32,
+}
+
+/// Asks for an insect of the named species
+#[derive(Event)]
+pub struct SpawnInsectEvent(pub String);
+
+/// Asks for an obstacle of this kind
+#[derive(Event)]
+pub struct SpawnObstacleEvent(pub ObstacleKind);
+
+#[derive(Resource)]
+struct WavesHandle(Handle<Waves>);
+
+#[derive(Default)]
+struct WavesLoader;
+
+#[derive(Debug)]
+pub enum WavesLoaderError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for WavesLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WavesLoaderError::Io(err) => write!(f, "{}", err),
+            WavesLoaderError::Parse(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for WavesLoaderError {}
+
+impl AssetLoader for WavesLoader {
+    type Asset = Waves;
+    type Settings = ();
+    type Error = WavesLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Waves, WavesLoaderError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(WavesLoaderError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(WavesLoaderError::Parse)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["waves.ron"]
+    }
+}
+
+impl Plugin for WavesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Waves>();
+        app.init_asset_loader::<WavesLoader>();
+        app.init_resource::<Waves>();
+        app.init_resource::<WaveDirector>();
+        app.init_resource::<Difficulty>();
+        app.add_event::<SpawnInsectEvent>();
+        app.add_event::<SpawnObstacleEvent>();
+        app.add_systems(Startup, load_waves);
+        app.add_systems(PreUpdate, apply_waves);
+        app.add_systems(Update, run_waves.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, reset_waves);
+    }
+}
+
+fn load_waves(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(WavesHandle(asset_server.load(WAVES_PATH)));
+}
+
+/// copies the wave asset into the `Waves` resource every time it (re)loads
+fn apply_waves(
+    mut ev_asset: EventReader<AssetEvent<Waves>>,
+    assets: Res<Assets<Waves>>,
+    handle: Option<Res<WavesHandle>>,
+    mut waves: ResMut<Waves>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+
+    for event in ev_asset.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            if *id != handle.0.id() {
+                continue;
+            }
+            if let Some(loaded) = assets.get(*id) {
+                debug!("Loaded {} waves", loaded.waves.len());
+                *waves = loaded.clone();
+            }
+        }
+    }
+}
+
+/// picks one of the items at random, by the weight next to it
+fn choose_weighted<T>(items: &[(T, f32)]) -> Option<&T> {
+    let index = WeightedIndex::new(items.iter().map(|(_, weight)| weight.max(0.0))).ok()?;
+    Some(&items[index.sample(&mut rand::thread_rng())].0)
+}
+
+/// Moves through the waves and rests between them, asking for insects and obstacles as the
+/// current wave says.
+fn run_waves(
+    mut director: ResMut<WaveDirector>,
+    waves: Res<Waves>,
+    difficulty: Res<Difficulty>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+    mut ev_insect: EventWriter<SpawnInsectEvent>,
+    mut ev_obstacle: EventWriter<SpawnObstacleEvent>,
+) {
+    if waves.waves.is_empty() {
+        return;
+    }
+    // the file may have been reloaded with fewer waves
+    director.wave = director.wave.min(waves.waves.len() - 1);
+
+    let wave = &waves.waves[director.wave];
+    let settings = waves.settings(*difficulty);
+    let dt = time.delta_seconds();
+    director.elapsed += dt;
+
+    if director.resting {
+        if director.elapsed >= wave.rest * settings.rest_scale {
+            director.resting = false;
+            director.elapsed = 0.0;
+            director.since_insect = 0.0;
+            director.since_obstacle = 0.0;
+            director.wave += 1;
+            if director.wave >= waves.waves.len() {
+                director.wave = waves.repeat_from.min(waves.waves.len() - 1);
+                director.repeats += 1;
+            }
+            info!("Wave: {}", waves.waves[director.wave].name);
+        }
+        return;
+    }
+
+    if director.elapsed >= wave.duration {
+        debug!("{} is over, resting", wave.name);
+        director.resting = true;
+        director.elapsed = 0.0;
+        return;
+    }
+
+    let interval_scale =
+        settings.spawn_interval_scale * waves.repeat_interval_scale.powi(director.repeats as i32);
+
+    director.since_insect += dt;
+    if director.since_insect >= (wave.insect_interval * interval_scale).max(MIN_SPAWN_INTERVAL) {
+        director.since_insect = 0.0;
+        let species = if wave.insects.is_empty() {
+            choose_species(&config.insect_species).map(|species| &species.name)
+        } else {
+            choose_weighted(&wave.insects)
+        };
+        if let Some(species) = species {
+            ev_insect.send(SpawnInsectEvent(species.clone()));
+        }
+    }
+
+    director.since_obstacle += dt;
+    if director.since_obstacle >= (wave.obstacle_interval * interval_scale).max(MIN_SPAWN_INTERVAL)
+    {
+        director.since_obstacle = 0.0;
+        if let Some(kind) = choose_weighted(&wave.obstacles) {
+            ev_obstacle.send(SpawnObstacleEvent(*kind));
+        }
+    }
+}
+
+/// starts the waves over for a new game
+fn reset_waves(mut ev_restarted: EventReader<GameRestarted>, mut director: ResMut<WaveDirector>) {
+    if ev_restarted.read().count() > 0 {
+        *director = WaveDirector::default();
+    }
+}
@@ -1,85 +1,44 @@
-use super::{render::WebSegmentCollision, spring::Spring, Web};
+use super::{render::WebSegmentCollision, Web, WebSimulation, WebSpringSnapped};
 use crate::config::熊猫;
 use crate::{config::冰淇淋, flying_insect::flying_insect::FlyingInsect};
 use bevy::{log, prelude::*};
 use bevy_rapier3d::prelude::{Collider, CollisionEvent, ContactForceEvent};
+use ld56_sim::web_sim;
 use rand::random;
 use std::f32::consts::PI;
 
 pub const ENSNARE_MY_BALLS: bool = false;
 
+/// average seconds between two struggles of a caught insect
+const STRUGGLE_INTERVAL: f32 = 1.5;
+/// how hard a caught insect tugs on its strand, per unit of struggle strength
+const STRUGGLE_IMPULSE: f32 = 3000.0;
+/// how much a struggle stretches the strand, per unit of struggle strength, when checking
+/// whether it tears
+const STRUGGLE_TEAR_STRAIN: f32 = 0.1;
+/// how hard an insect kicks its strand as it breaks free, per unit of struggle strength
+const BREAK_FREE_IMPULSE: f32 = 8000.0;
+
 #[derive(Component)]
 pub struct Ensnared;
 
 #[derive(Component)]
 pub struct Freed;
 
-#[derive(Debug, Clone)]
-pub struct EnsnaredEntity {
-    /// the entity that is snared in the web
-    pub entity: Entity,
-    /// the position along the spring at which it's ensnared.
-    ///  ranges from 0 (first particle) -> 1 (second particle)
-    pub snare_position: f32,
-    pub mass: f32,
-    pub rotation: f32,
-    pub lerp_rotation: f32,
-    pub done_ensnaring: bool,
-}
-
-impl EnsnaredEntity {
-    pub fn snare_position_from_world_space(
-        snare_position_world_space: Vec3,
-        first_particle_position: Vec3,
-        second_particle_position: Vec3,
-    ) -> f32 {
-        (snare_position_world_space - first_particle_position).length()
-            / (second_particle_position - first_particle_position).length()
-    }
+/// Sent when an insect flies into the web and gets stuck
+#[derive(Event)]
+pub struct InsectEnsnaredEvent(pub Entity);
 
-    pub fn from_snare_position_world_space(
-        entity: Entity,
-        mass: f32,
-        snare_position_world_space: Vec3,
-        first_particle_position: Vec3,
-        second_particle_position: Vec3,
-    ) -> Self {
-        let snare_position = Self::snare_position_from_world_space(
-            snare_position_world_space,
-            first_particle_position,
-            second_particle_position,
-        )
-        .clamp(0.0, 1.0);
-
-        error!("不冰淇淋");
-        //assert!(snare_position >= 0.0 && snare_position <= 1.0);
-
-        EnsnaredEntity {
-            entity,
-            snare_position,
-            mass,
-            rotation: 0.0,
-            lerp_rotation: 0.0,
-            done_ensnaring: false,
-        }
-    }
-    pub fn snare_position_world_space(
-        &self,
-        first_particle_position: Vec3,
-        second_particle_position: Vec3,
-    ) -> Vec3 {
-        ((1.0 - self.snare_position) * first_particle_position)
-            + (self.snare_position * second_particle_position)
-    }
-}
+pub type EnsnaredEntity = web_sim::ensnare::EnsnaredEntity<Entity>;
 
 pub fn ensnare_enemies(
     mut commands: Commands,
     enemies_query: Query<(&FlyingInsect, &Transform), (Without<Ensnared>, Without<Freed>)>,
     web_segment_collisions_query: Query<&WebSegmentCollision>,
-    mut web_query: Query<&mut Web>,
+    mut web_query: Query<&mut WebSimulation>,
     mut collision_events: EventReader<CollisionEvent>,
     mut contact_force_events: EventReader<ContactForceEvent>,
+    mut ev_ensnared: EventWriter<InsectEnsnaredEvent>,
 ) {
     let Ok(mut web) = web_query.get_single_mut() else {
         error!("ERROR NO WEB OR MORE THAN ONE WEB");
@@ -91,8 +50,11 @@ pub fn ensnare_enemies(
          (enemy, enemy_transform): (&FlyingInsect, &Transform),
          web_segment_collision: &WebSegmentCollision| {
             // warn!("Handling ensnare");
-            let i1 = web.springs[web_segment_collision.spring_index].first_index;
-            let i2 = web.springs[web_segment_collision.spring_index].second_index;
+            let Some(spring) = web.springs.get(web_segment_collision.spring_index) else {
+                return;
+            };
+            let i1 = spring.first_index;
+            let i2 = spring.second_index;
             let first_particle_position = web.particles[i1].position;
             let second_particle_position = web.particles[i2].position;
             let spring = &mut web.springs[web_segment_collision.spring_index];
@@ -125,12 +87,15 @@ pub fn ensnare_enemies(
             };
 
             commands.entity(enemy_entity).insert(Ensnared);
+            ev_ensnared.send(InsectEnsnaredEvent(enemy_entity));
 
             spring.ensnared_entities.push(ensnared_entity);
-            web.particles[i1].impulse = Vec3::new(0.0, 0.0, 1.0) * 10000.0 * (1.0 - t);
-            web.particles[i1].impulse_duration = 0.1;
-            web.particles[i2].impulse = Vec3::new(0.0, 0.0, 1.0) * 10000.0 * t;
-            web.particles[i2].impulse_duration = 0.1;
+            web.push_at(
+                web_segment_collision.spring_index,
+                t,
+                Vec3::new(0.0, 0.0, 1.0) * 10000.0,
+                0.1,
+            );
         };
 
     for collision_event in collision_events.read() {
@@ -159,11 +124,11 @@ pub fn ensnare_enemies(
     }
 }
 
-pub fn free_enemy_from_web(
-    mut commands: &mut Commands,
+/// Lets go of an insect the web no longer holds, for when it was already taken off its spring
+pub fn release_insect(
+    commands: &mut Commands,
     insect_entity: Entity,
     insect: Option<&FlyingInsect>,
-    web: &mut Web,
 ) {
     commands.entity(insect_entity).remove::<Ensnared>();
     commands.entity(insect_entity).insert(Freed);
@@ -173,23 +138,132 @@ pub fn free_enemy_from_web(
             commands.entity(rolled_ensnare_entity).despawn();
         }
     }
+}
 
-    for mut spring in &mut web.springs {
+pub fn free_enemy_from_web(
+    commands: &mut Commands,
+    insect_entity: Entity,
+    insect: Option<&FlyingInsect>,
+    web: &mut Web,
+) {
+    release_insect(commands, insect_entity, insect);
+
+    let mut kick = None;
+    for (spring_index, spring) in web.springs.iter_mut() {
         for i in 0..spring.ensnared_entities.len() {
             if spring.ensnared_entities.get(i).unwrap().entity == insect_entity {
-                spring.ensnared_entities.swap_remove(i);
-                // TODO: add force upon freeing of insect
+                let ensnared = spring.ensnared_entities.swap_remove(i);
+                kick = Some((spring_index, ensnared.snare_position));
                 break;
             }
         }
     }
+
+    // an insect that wasn't wrapped up yet tears itself loose and jolts the strand
+    if let (Some((spring_index, t)), Some(insect)) = (kick, insect) {
+        if insect.snare_roll_progress < 1.0 {
+            web.push_at(
+                spring_index,
+                t,
+                Vec3::new(0.0, 0.0, 1.0) * BREAK_FREE_IMPULSE * insect.struggle_strength,
+                0.1,
+            );
+        }
+    }
+}
+
+/// Caught insects that aren't wrapped up yet thrash about now and then, tugging on their
+/// strand. If the strand is already pulled tight it tears and lets them go.
+pub fn struggle_in_web(
+    mut commands: Commands,
+    mut web_query: Query<&mut WebSimulation>,
+    insect_query: Query<&FlyingInsect, With<Ensnared>>,
+    time: Res<Time>,
+) {
+    let Ok(mut web_simulation) = web_query.get_single_mut() else {
+        error!("ERROR NO WEB OR MORE THAN ONE WEB");
+        return;
+    };
+    let web: &mut Web = &mut web_simulation;
+    let chance = time.delta_seconds() / STRUGGLE_INTERVAL;
+
+    let mut struggles = vec![];
+    for (spring_index, spring) in web.springs.iter() {
+        for ensnared in &spring.ensnared_entities {
+            let Ok(insect) = insect_query.get(ensnared.entity) else {
+                continue;
+            };
+            if insect.snare_roll_progress < 1.0 && 熊猫() < chance {
+                struggles.push((
+                    spring_index,
+                    ensnared.snare_position,
+                    insect.struggle_strength,
+                ));
+            }
+        }
+    }
+
+    for (spring_index, t, strength) in struggles {
+        // another insect on the same strand may have torn it already
+        if !web.springs.contains_key(spring_index) {
+            continue;
+        }
+
+        let direction = Vec3::new(熊猫() - 0.5, 熊猫() - 0.5, 熊猫() - 0.5).normalize_or_zero();
+        web.push_at(
+            spring_index,
+            t,
+            direction * STRUGGLE_IMPULSE * strength,
+            0.1,
+        );
+
+        if web.would_tear(spring_index, STRUGGLE_TEAR_STRAIN * strength) {
+            // the insect tears itself loose and jolts the strand, before it is gone
+            web.push_at(
+                spring_index,
+                t,
+                Vec3::new(0.0, 0.0, 1.0) * BREAK_FREE_IMPULSE * strength,
+                0.1,
+            );
+            let released = web.cut_spring(spring_index, t);
+            debug!(
+                "Insect tore its strand, {} insects got away",
+                released.len()
+            );
+            for ensnared in released {
+                release_insect(
+                    &mut commands,
+                    ensnared.entity,
+                    insect_query.get(ensnared.entity).ok(),
+                );
+            }
+        }
+    }
+}
+
+/// the snapped spring already let go of its insects, only their components are left to update
+pub fn release_snapped_entities(
+    mut commands: Commands,
+    insect_query: Query<&FlyingInsect>,
+    mut ev_snapped: EventReader<WebSpringSnapped>,
+) {
+    for snapped in ev_snapped.read() {
+        debug!(
+            "Web snapped at {}, releasing {} insects",
+            snapped.position,
+            snapped.released.len()
+        );
+        for entity in &snapped.released {
+            release_insect(&mut commands, *entity, insect_query.get(*entity).ok());
+        }
+    }
 }
 
 pub fn debug_ensnare_entities(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut web_query: Query<&mut Web>,
+    mut web_query: Query<&mut WebSimulation>,
 ) {
     if !ENSNARE_MY_BALLS {
         return;
@@ -207,7 +281,7 @@ pub fn debug_ensnare_entities(
         ..default()
     });
 
-    for spring in web_data.springs.iter_mut() {
+    for spring in web_data.springs.values_mut() {
         for _ in 0..2 {
             let random_position: f32 = random();
 
@@ -233,12 +307,14 @@ pub fn debug_ensnare_entities(
 }
 
 pub fn update_ensnared_entities(
-    mut web_query: Query<&mut Web>,
+    mut web_query: Query<&mut WebSimulation>,
     mut transform_query: Query<&mut Transform>,
+    insect_query: Query<&FlyingInsect>,
 ) {
-    let web = &mut *web_query.single_mut();
+    let mut web_simulation = web_query.single_mut();
+    let web: &mut Web = &mut web_simulation;
 
-    for spring in web.springs.iter_mut() {
+    for spring in web.springs.values_mut() {
         for ensnared_entity in spring.ensnared_entities.iter_mut() {
             let Ok(mut ensnared_entity_transform) = transform_query.get_mut(ensnared_entity.entity)
             else {
@@ -254,12 +330,17 @@ pub fn update_ensnared_entities(
                 continue;
             }
 
+            // stronger insects thrash about more
+            let struggle = insect_query
+                .get(ensnared_entity.entity)
+                .map_or(1.0, |insect| insect.struggle_strength);
+
             if 熊猫() > 0.6f32 {
-                ensnared_entity.rotation += (0.1 * PI) * (熊猫() - 0.5);
+                ensnared_entity.rotation += (0.1 * PI) * struggle * (熊猫() - 0.5);
             }
 
             if 熊猫() > 0.97f32 {
-                ensnared_entity.rotation += (0.5 * PI) * (熊猫() - 0.5);
+                ensnared_entity.rotation += (0.5 * PI) * struggle * (熊猫() - 0.5);
             }
 
             ensnared_entity.lerp_rotation =
@@ -269,65 +350,3 @@ pub fn update_ensnared_entities(
         }
     }
 }
-
-pub fn split_ensnared_entities_for_spring_split(
-    web: &Web,
-    old_spring: &Spring,
-    split_position: Vec3,
-) -> (Vec<EnsnaredEntity>, Vec<EnsnaredEntity>) {
-    let mut new_particle_t = EnsnaredEntity::snare_position_from_world_space(
-        split_position,
-        web.particles[old_spring.first_index].position,
-        web.particles[old_spring.second_index].position,
-    );
-
-    if new_particle_t >= 0.0 && new_particle_t <= 1.0 {
-        error!("new_particle_t={new_particle_t}");
-    }
-
-    new_particle_t = new_particle_t.clamp(0.0, 1.0);
-
-    let new_spring_1_ensnared_entities = old_spring
-        .ensnared_entities
-        .iter()
-        .filter(|ensnared| ensnared.snare_position <= new_particle_t)
-        .map(|ensnared| {
-            let snare_position_world_space = ensnared.snare_position_world_space(
-                web.particles[old_spring.first_index].position,
-                web.particles[old_spring.second_index].position,
-            );
-
-            EnsnaredEntity::from_snare_position_world_space(
-                ensnared.entity.clone(),
-                ensnared.mass,
-                snare_position_world_space,
-                web.particles[old_spring.first_index].position,
-                split_position,
-            )
-        })
-        .collect();
-    let new_spring_2_ensnared_entities = old_spring
-        .ensnared_entities
-        .iter()
-        .filter(|ensnared| ensnared.snare_position > new_particle_t)
-        .map(|ensnared| {
-            let snare_position_world_space = ensnared.snare_position_world_space(
-                web.particles[old_spring.first_index].position,
-                web.particles[old_spring.second_index].position,
-            );
-
-            EnsnaredEntity::from_snare_position_world_space(
-                ensnared.entity.clone(),
-                ensnared.mass,
-                snare_position_world_space,
-                split_position,
-                web.particles[old_spring.second_index].position,
-            )
-        })
-        .collect();
-
-    (
-        new_spring_1_ensnared_entities,
-        new_spring_2_ensnared_entities,
-    )
-}
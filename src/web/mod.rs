@@ -1,55 +1,104 @@
 pub mod ensnare;
-mod render;
+pub mod render;
+pub mod save;
 pub mod spring;
 
+use crate::config::GameConfig;
+use crate::controls::{ControlAction, ControlState};
 use crate::flying_insect::flying_insect::FlyingInsect;
-use crate::flying_obstacle::flying_obstacle::FlyingObstacle;
+use crate::flying_obstacle::flying_obstacle::{FlyingObstacle, WebEffect};
+use crate::game::{GameRestarted, GameState};
 use crate::tree::{get_arena_center, 照相机里有点吗};
-use crate::web::ensnare::{free_enemy_from_web, split_ensnared_entities_for_spring_split};
+use crate::web::ensnare::{free_enemy_from_web, release_insect};
 use crate::web::render::WebSegmentCollision;
-use crate::web::spring::Spring;
+use crate::web::spring::{Spring, DEFAULT_MAX_STRAIN};
 use bevy::prelude::*;
 use bevy_rapier3d::pipeline::CollisionEvent;
 use bevy_rapier3d::prelude::Collider;
-use ensnare::{debug_ensnare_entities, ensnare_enemies, update_ensnared_entities};
-use render::{clear_web, render_web};
+use ensnare::{
+    debug_ensnare_entities, ensnare_enemies, release_snapped_entities, struggle_in_web,
+    update_ensnared_entities, InsectEnsnaredEvent,
+};
+use ld56_sim::web_sim::{self, step, SnappedSpring};
+pub use ld56_sim::web_sim::{integrator, path, Particle, ParticleKey, SpringKey};
+use render::{render_web, spawn_web_render_mesh, sync_web_colliders};
+use save::{load_web_from_file, save_load_web};
 use std::f32::consts::PI;
 
 pub const START_WITH_A_WEB: bool = false; // FOR NOOBS
+/// web layout to start with instead, takes priority over `START_WITH_A_WEB`
+pub const START_WEB_FILE: Option<&str> = None;
 pub static mut splitting_spring: i32 = 0;
 pub static mut destroy_call: i32 = 0;
 
 pub struct WebSimulationPlugin;
 
-pub struct Particle {
-    pub position: Vec3,
-    pub velocity: Vec3,
-    pub force: Vec3,
-    pub impulse: Vec3,
-    pub impulse_duration: f32,
-    pub mass: f32,
-    pub pinned: bool,
+/// share of the water on the web that drips off each second
+const WATER_DRIP_RATE: f32 = 0.2;
+
+#[derive(Resource)]
+pub struct WebSimulationSettings {
+    pub air_damping: f32,
+}
+
+/// Substeps the web may still run this frame. `FixedUpdate` catches up on every second of a
+/// long frame, the web only on its share of them and drops the rest.
+#[derive(Resource, Default)]
+struct WebStepBudget {
+    steps_left: u32,
 }
 
 #[derive(Component)]
 pub struct Breaker;
 
-#[derive(Component)]
-pub struct Web {
-    pub particles: Vec<Particle>,
-    pub springs: Vec<Spring>,
-    pub mass_per_unit_length: f32,
+/// Sent when the simulation snaps a spring that was stretched too far
+#[derive(Event)]
+pub struct WebSpringSnapped {
+    pub position: Vec3,
+    /// entities that were ensnared on the spring and are now free
+    pub released: Vec<Entity>,
+}
+
+impl From<SnappedSpring<Entity>> for WebSpringSnapped {
+    fn from(snapped: SnappedSpring<Entity>) -> Self {
+        WebSpringSnapped {
+            position: snapped.position,
+            released: snapped.released,
+        }
+    }
+}
+
+/// what cut through the web
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebCutCause {
+    Spider,
+    Obstacle,
+    Predator,
 }
 
-impl Web {
+/// Sent when springs are cut, by the spider or by something flying through the web
+#[derive(Event)]
+pub struct WebCutEvent {
+    pub springs_cut: usize,
+    pub cause: WebCutCause,
+}
+
+pub type Web = web_sim::Web<Entity>;
+
+/// The web the spider builds, wrapped so it can live on an entity
+#[derive(Component, Deref, DerefMut, Default)]
+pub struct WebSimulation(pub Web);
+
+impl WebSimulation {
+    /// cuts every spring passing through the point and returns how many were cut
     pub fn 破壊する(
         &mut self,
         ポイント: Vec3,
         insect_query: &Query<&FlyingInsect>,
         commands: &mut Commands,
-    ) {
+    ) -> usize {
         if !照相机里有点吗(ポイント) {
-            return;
+            return 0;
         }
 
         unsafe {
@@ -57,8 +106,9 @@ impl Web {
             destroy_call += 1;
         }
 
-        let カウント = self.springs.len();
-        for インデックス in 0..カウント {
+        let mut 切った数 = 0;
+        let キー: Vec<SpringKey> = self.springs.keys().collect();
+        for インデックス in キー {
             let 粒子1 = self.particles[self.springs[インデックス].first_index].position;
             let 粒子2 = self.particles[self.springs[インデックス].second_index].position;
 
@@ -87,162 +137,113 @@ impl Web {
                 );
             }
 
-            let あるバネのポイント =
-                粒子2 * あるバネのパラメーター + 粒子1 * (1.0 - あるバネのパラメーター);
-
-            self.particles.push(Particle {
-                position: あるバネのポイント,
-                velocity: Default::default(),
-                force: Default::default(),
-                impulse: Vec3::new(0.0, 0.0, 1.0) * 10000.0,
-                impulse_duration: 0.1,
-                mass: 0.0,
-                pinned: false,
-            });
-
-            self.particles.push(Particle {
-                position: あるバネのポイント,
-                velocity: Default::default(),
-                force: Default::default(),
-                impulse: Vec3::new(0.0, 0.0, 1.0) * 10000.0,
-                impulse_duration: 0.1,
-                mass: 0.0,
-                pinned: false,
-            });
-
-            let 新粒子1 = self.particles.len() - 2;
-            let 新粒子2 = self.particles.len() - 1;
-
-            self.springs.push(Spring {
-                first_index: self.springs[インデックス].first_index,
-                second_index: 新粒子1,
-                stiffness: self.springs[インデックス].stiffness,
-                damping: self.springs[インデックス].damping,
-                rest_length: self.springs[インデックス].rest_length
-                    * あるバネのパラメーター,
-                ensnared_entities: vec![],
-            });
-
-            self.springs.push(Spring {
-                first_index: 新粒子2,
-                second_index: self.springs[インデックス].second_index,
-                stiffness: self.springs[インデックス].stiffness,
-                damping: self.springs[インデックス].damping,
-                rest_length: self.springs[インデックス].rest_length
-                    * (1.0 - あるバネのパラメーター),
-                ensnared_entities: vec![],
-            });
-
-            self.springs.swap_remove(インデックス);
+            self.cut_spring(インデックス, あるバネのパラメーター);
+            切った数 += 1;
         }
-    }
-}
-
-impl Web {
-    pub fn get_spring(&self, p1: usize, p2: usize) -> Option<usize> {
-        for i in 0..self.springs.len() {
-            let spring = &self.springs[i];
-            if spring.first_index == p1 && spring.second_index == p2
-                || spring.first_index == p2 && spring.second_index == p1
-            {
-                return Some(i);
-            }
-        }
-        None
-    }
-}
-
-impl Default for Web {
-    fn default() -> Self {
-        Web {
-            particles: vec![],
-            springs: vec![],
-            mass_per_unit_length: 0.1,
-        }
-    }
-}
-
-impl Web {
-    pub fn get_particle_index(&self, pos: Vec3, ε: f32) -> Option<usize> {
-        for i in 0..self.particles.len() {
-            if self.particles[i].position.distance_squared(pos) < ε * ε {
-                return Some(i);
-            }
-        }
-        None
-    }
-
-    pub fn split_spring(&mut self, spring_index: usize, position: Vec3) {
-        self.particles.push(Particle {
-            position: position,
-            velocity: Default::default(),
-            force: Default::default(),
-            impulse: Default::default(),
-            impulse_duration: 0.0,
-            mass: 0.0,
-            pinned: false,
-        });
-
-        let old_spring: Spring = self.springs.swap_remove(spring_index);
-        let t = (position - self.particles[old_spring.first_index].position).length()
-            / (self.particles[old_spring.second_index].position
-                - self.particles[old_spring.first_index].position)
-                .length();
-
-        let (new_spring_1_ensnared_entities, new_spring_2_ensnared_entities) =
-            split_ensnared_entities_for_spring_split(&self, &old_spring, position);
-
-        self.springs.push(Spring::new_with_length(
-            self,
-            old_spring.first_index,
-            self.particles.len() - 1,
-            20.0,
-            0.5,
-            old_spring.rest_length * t,
-            new_spring_1_ensnared_entities,
-        ));
-        self.springs.push(Spring::new_with_length(
-            self,
-            self.particles.len() - 1,
-            old_spring.second_index,
-            20.0,
-            0.5,
-            old_spring.rest_length * (1.0 - t),
-            new_spring_2_ensnared_entities,
-        ));
+        切った数
     }
 }
 
 impl Plugin for WebSimulationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_simulation);
-        app.add_systems(Update, update_simulation);
+        app.insert_resource(WebSimulationSettings { air_damping: 0.5 });
+        app.init_resource::<WebStepBudget>();
+        app.add_event::<WebSpringSnapped>();
+        app.add_event::<WebCutEvent>();
+        app.add_event::<InsectEnsnaredEvent>();
 
-        app.add_systems(Update, clear_web);
-        app.add_systems(Update, render_web.after(clear_web));
+        app.add_systems(Startup, spawn_simulation);
+        app.add_systems(
+            PreUpdate,
+            apply_web_timestep.run_if(resource_changed::<GameConfig>),
+        );
+        app.add_systems(PreUpdate, refill_web_step_budget);
+        app.add_systems(
+            FixedUpdate,
+            update_simulation
+                .run_if(in_state(GameState::Game))
+                .run_if(web_steps_left),
+        );
+        app.add_systems(Update, restart_simulation);
+
+        app.add_systems(Startup, spawn_web_render_mesh);
+        app.add_systems(Update, sync_web_colliders);
+        app.add_systems(Update, render_web.after(sync_web_colliders));
 
         app.add_systems(Startup, debug_ensnare_entities.after(spawn_simulation));
-        app.add_systems(Update, ensnare_enemies);
-        app.add_systems(Update, update_ensnared_entities);
-
-        app.add_systems(Update, handle_obstacles_destroy_web);
+        app.add_systems(Update, ensnare_enemies.run_if(in_state(GameState::Game)));
+        app.add_systems(
+            Update,
+            update_ensnared_entities.run_if(in_state(GameState::Game)),
+        );
+        app.add_systems(Update, struggle_in_web.run_if(in_state(GameState::Game)));
+        app.add_systems(Update, release_snapped_entities);
+        app.add_systems(
+            Update,
+            compact_web
+                .after(release_snapped_entities)
+                .before(sync_web_colliders),
+        );
+
+        app.add_systems(
+            Update,
+            handle_obstacles_destroy_web.run_if(in_state(GameState::Game)),
+        );
+        app.add_systems(
+            FixedUpdate,
+            drip_web
+                .before(update_simulation)
+                .run_if(in_state(GameState::Game))
+                .run_if(web_steps_left),
+        );
+        app.add_systems(Update, cycle_integrator);
+        app.add_systems(Update, save_load_web);
     }
 }
 
 fn spawn_simulation(mut commands: Commands) {
     println!("WebSimulationPlugin init");
-    let web = if START_WITH_A_WEB {
+    commands.spawn(WebSimulation(new_web()));
+}
+
+/// throws away the old web and starts over with the one a new game starts with
+fn restart_simulation(
+    mut commands: Commands,
+    mut ev_restarted: EventReader<GameRestarted>,
+    web_query: Query<Entity, With<WebSimulation>>,
+) {
+    if ev_restarted.read().count() == 0 {
+        return;
+    }
+
+    for entity in &web_query {
+        commands.entity(entity).despawn();
+    }
+    commands.spawn(WebSimulation(new_web()));
+}
+
+fn new_web() -> Web {
+    let from_file = START_WEB_FILE.and_then(|path| match load_web_from_file(path) {
+        // a starting web comes without insects
+        Ok((web, _)) => Some(web),
+        Err(err) => {
+            error!("Could not load starting web from {}: {}", path, err);
+            None
+        }
+    });
+    if let Some(web) = from_file {
+        web
+    } else if START_WITH_A_WEB {
         generate_web(4, 8, 1.0, 0.1, 30.0, 0.5)
     } else {
         Default::default()
-    };
-    commands.spawn(web);
+    }
 }
 
 fn generate_2_particle_example() -> Web {
     let arena_center = get_arena_center();
     let mut web: Web = Default::default();
-    web.particles.push(Particle {
+    let first_index = web.particles.insert(Particle {
         position: arena_center + Vec3::new(0.0, 0.0, 0.0),
         velocity: Default::default(),
         force: Default::default(),
@@ -251,7 +252,7 @@ fn generate_2_particle_example() -> Web {
         mass: 0.0,
         pinned: false,
     });
-    web.particles.push(Particle {
+    let second_index = web.particles.insert(Particle {
         position: arena_center + Vec3::new(0.0, 1.0, 0.0),
         velocity: Default::default(),
         force: Default::default(),
@@ -260,12 +261,13 @@ fn generate_2_particle_example() -> Web {
         mass: 0.0,
         pinned: true,
     });
-    web.springs.push(Spring {
-        first_index: 0,
-        second_index: 1,
+    web.springs.insert(Spring {
+        first_index,
+        second_index,
         stiffness: 100.0,
         damping: 1.0,
         rest_length: 1.0,
+        max_strain: DEFAULT_MAX_STRAIN,
         ensnared_entities: vec![],
     });
     web
@@ -282,7 +284,7 @@ fn generate_web(
     let arena_center = get_arena_center();
     let mut web: Web = Default::default();
     web.mass_per_unit_length = mass_density;
-    web.particles.push(Particle {
+    let center = web.particles.insert(Particle {
         position: arena_center,
         velocity: Default::default(),
         force: Default::default(),
@@ -291,14 +293,11 @@ fn generate_web(
         mass: 0.0,
         pinned: false,
     });
+    let mut ring: Vec<ParticleKey> = vec![];
     for i in 0..row_count {
+        let mut new_ring: Vec<ParticleKey> = vec![];
         for j in 0..col_count {
-            let left = if i == 0 {
-                0
-            } else {
-                web.particles.len() - col_count
-            };
-            let prev = web.particles.len() - 1;
+            let left = if i == 0 { center } else { ring[j] };
 
             let r = (i as f32 + 1.0) / row_count as f32 * size;
             let θ = j as f32 / col_count as f32 * 2.0 * PI;
@@ -307,7 +306,7 @@ fn generate_web(
 
             let pos = arena_center + dir * r;
 
-            web.particles.push(Particle {
+            let new = web.particles.insert(Particle {
                 position: pos,
                 velocity: Default::default(),
                 force: Default::default(),
@@ -317,178 +316,223 @@ fn generate_web(
                 pinned: i == row_count - 1,
             });
 
-            let new = web.particles.len() - 1;
-
             web.springs
-                .push(Spring::new(&web, new, left, stiffness, damping, vec![]));
+                .insert(Spring::new(&web, new, left, stiffness, damping, vec![]));
 
             if i != row_count - 1 && j != 0 {
+                let prev = new_ring[j - 1];
                 web.springs
-                    .push(Spring::new(&web, new, prev, stiffness, damping, vec![]));
+                    .insert(Spring::new(&web, new, prev, stiffness, damping, vec![]));
 
                 if j == col_count - 1 {
-                    web.springs.push(Spring::new(
+                    web.springs.insert(Spring::new(
                         &web,
                         new,
-                        web.particles.len() - col_count,
+                        new_ring[0],
                         stiffness,
                         damping,
                         vec![],
                     ));
                 }
             }
-        }
-    }
-    web
-}
-
-fn update_simulation(mut query: Query<&mut Web>, time: Res<Time>) {
-    let h = time.delta_seconds();
-    let desired_h = 0.001;
-    let count: i32 = (h / desired_h).ceil() as i32;
-    let air_damping = 0.5;
 
-    for i in 0..count {
-        for mut web in &mut query {
-            step(
-                &mut *web,
-                air_damping,
-                if i == count - 1 {
-                    h - (count - 1) as f32 * desired_h
-                } else {
-                    desired_h
-                },
-            );
+            new_ring.push(new);
         }
+        ring = new_ring;
     }
+    web
 }
 
-pub fn step(web: &mut Web, air_damping: f32, h: f32) {
-    for i in 0..web.particles.len() {
-        if web.particles[i].pinned {
-            continue;
-        }
-        web.particles[i].mass = 0.0;
-        web.particles[i].force = Vec3::new(0.0, 0.0, 0.0);
+/// Sets the fixed timestep to the web substep from the config
+fn apply_web_timestep(config: Res<GameConfig>, mut fixed_time: ResMut<Time<Fixed>>) {
+    if config.web_substep <= 0.0 || config.web_max_substeps_per_frame == 0 {
+        error!(
+            "Web substep {} x {} per frame is not a usable timestep",
+            config.web_substep, config.web_max_substeps_per_frame
+        );
+        return;
     }
 
-    for j in 0..web.springs.len() {
-        let force = web.springs[j].get_force_p1(&web);
-        let p1 = web.springs[j].first_index;
-        let p2 = web.springs[j].second_index;
+    fixed_time.set_timestep_seconds(config.web_substep as f64);
+}
 
-        // calculate mass of ensnared_entities
-        for ensnared in web.springs[j].ensnared_entities.clone() {
-            if !web.particles[p1].pinned {
-                web.particles[p1].mass += ensnared.mass * (1.0 - ensnared.snare_position);
-            }
+/// allows `web_max_substeps_per_frame` more substeps, before this frame's `FixedUpdate` runs
+fn refill_web_step_budget(config: Res<GameConfig>, mut budget: ResMut<WebStepBudget>) {
+    budget.steps_left = config.web_max_substeps_per_frame;
+}
 
-            if !web.particles[p2].pinned {
-                web.particles[p2].mass += ensnared.mass * (ensnared.snare_position);
-            }
-        }
+fn web_steps_left(budget: Res<WebStepBudget>) -> bool {
+    budget.steps_left > 0
+}
 
-        if !web.particles[p1].pinned {
-            web.particles[p1].force += force;
-            web.particles[p1].mass += web.mass_per_unit_length * web.springs[j].rest_length / 2.0;
-        }
-        if !web.particles[p2].pinned {
-            web.particles[p2].force -= force;
-            web.particles[p2].mass += web.mass_per_unit_length * web.springs[j].rest_length / 2.0;
+/// Advances the web by one substep, runs on `FixedUpdate` so the web moves the same whatever
+/// the frame rate
+fn update_simulation(
+    mut query: Query<&mut WebSimulation>,
+    settings: Res<WebSimulationSettings>,
+    mut ev_snapped: EventWriter<WebSpringSnapped>,
+    mut budget: ResMut<WebStepBudget>,
+    time: Res<Time>,
+) {
+    budget.steps_left = budget.steps_left.saturating_sub(1);
+    for mut web in &mut query {
+        step(&mut web, settings.air_damping, time.delta_seconds());
+        for snapped in web.snap_overstretched_springs() {
+            ev_snapped.send(snapped.into());
         }
     }
+}
 
-    for particle in &mut web.particles {
-        if particle.pinned {
-            continue;
-        }
+/// Drops the parts of the web that came loose, only after something was cut
+fn compact_web(
+    mut commands: Commands,
+    mut web_query: Query<&mut WebSimulation>,
+    insect_query: Query<&FlyingInsect>,
+) {
+    let Ok(mut web) = web_query.get_single_mut() else {
+        error!("ERROR NO WEB OR MORE THAN ONE WEB");
+        return;
+    };
+    if !web.needs_compact {
+        return;
+    }
 
-        particle.force.y -= 9.81 * particle.mass;
-        particle.force += particle.velocity * -air_damping;
+    for entity in web.compact() {
+        release_insect(&mut commands, entity, insect_query.get(entity).ok());
+    }
+}
 
-        if particle.impulse_duration > 0.0 {
-            particle.force += particle.impulse * h;
-            particle.impulse_duration -= h;
-            if particle.impulse_duration <= 0.0 {
-                particle.impulse = Vec3::ZERO;
-                particle.impulse_duration = 0.0;
-            }
+fn cycle_integrator(mut query: Query<&mut WebSimulation>, controls: Res<ControlState>) {
+    if controls.just_pressed(ControlAction::CycleIntegrator) {
+        for mut web in &mut query {
+            web.integrator = web.integrator.next();
+            info!("Web integrator: {:?}", web.integrator);
         }
-
-        particle.velocity += particle.force / particle.mass * h;
-        particle.position += particle.velocity * h;
     }
 }
 
 fn handle_obstacles_destroy_web(
     mut commands: Commands,
-    mut web_query: Query<&mut Web>,
+    mut web_query: Query<&mut WebSimulation>,
     insect_query: Query<&FlyingInsect>,
     mut collision_events: EventReader<CollisionEvent>,
     web_segment_collisions_query: Query<&WebSegmentCollision>,
     mut obstacle_query: Query<(&mut FlyingObstacle, &mut Transform), Without<Breaker>>,
+    mut ev_cut: EventWriter<WebCutEvent>,
 ) {
     let Ok(mut web) = web_query.get_single_mut() else {
         panic!("FUCK NO WEB");
     };
 
     let mut call_counter = 0;
+    let mut handled: Vec<Entity> = vec![];
+
+    let mut handle_web_hit = |web: &mut WebSimulation,
+                              web_segment: &WebSegmentCollision,
+                              obstacle: &mut FlyingObstacle,
+                              obstacle_trans: Vec3,
+                              entity: Entity| {
+        // the spring may already be gone if something else cut it this frame
+        let Some(spring) = web.springs.get(web_segment.spring_index) else {
+            return;
+        };
+        let first_particle_position = web.particles[spring.first_index].position;
+        let second_particle_position = web.particles[spring.second_index].position;
 
-    let mut handle_spring_break =
-        |web: &mut Web, web_segment: &WebSegmentCollision, obstacle_trans: Vec3, entity: Entity| {
-            let spring = web.springs.get(web_segment.spring_index).unwrap();
-            let first_particle_position = web.particles.get(spring.first_index).unwrap().position;
-            let second_particle_position = web.particles.get(spring.second_index).unwrap().position;
-
-            let obstacle_position_t = (obstacle_trans - first_particle_position)
-                .dot(second_particle_position - first_particle_position)
-                / (second_particle_position - first_particle_position)
-                    .dot(second_particle_position - first_particle_position);
+        let obstacle_position_t = (obstacle_trans - first_particle_position)
+            .dot(second_particle_position - first_particle_position)
+            / (second_particle_position - first_particle_position)
+                .dot(second_particle_position - first_particle_position);
 
-            if obstacle_position_t < -0.1 || obstacle_position_t > 1.1 {
-                error!(
-                    "不冰淇淋, \
+        if obstacle_position_t < -0.1 || obstacle_position_t > 1.1 {
+            error!(
+                "不冰淇淋, \
             first_particle_position={first_particle_position}, \
             second_particle_position={second_particle_position}, \
             obstacle_trans={obstacle_trans}, \
             obstacle_position_t={obstacle_position_t}"
+            );
+        }
+
+        call_counter += 1;
+        assert!(call_counter < 10000);
+
+        let t = obstacle_position_t.clamp(0.0, 1.0);
+
+        match &mut obstacle.effect {
+            WebEffect::Cut(cuts_left) => {
+                let obstacle_position =
+                    ((1.0 - t) * first_particle_position) + (t * second_particle_position);
+                let springs_cut = web.破壊する(obstacle_position, &insect_query, &mut commands);
+                if springs_cut > 0 {
+                    ev_cut.send(WebCutEvent {
+                        springs_cut,
+                        cause: WebCutCause::Obstacle,
+                    });
+                }
+                *cuts_left = cuts_left.saturating_sub(1);
+                if *cuts_left == 0 {
+                    commands.entity(entity).insert(Breaker);
+                    commands.entity(entity).remove::<Collider>();
+                }
+            }
+            WebEffect::Push(push) => {
+                web.push_at(
+                    web_segment.spring_index,
+                    t,
+                    Vec3::new(0.0, -*push, 0.0),
+                    0.1,
                 );
+                commands.entity(entity).insert(Breaker);
+                commands.entity(entity).remove::<Collider>();
             }
-
-            call_counter += 1;
-            assert!(call_counter < 10000);
-
-            let t = obstacle_position_t.clamp(0.0, 1.0);
-
-            let obstacle_position =
-                ((1.0 - t) * first_particle_position) + (t * second_particle_position);
-            web.破壊する(obstacle_position, &insect_query, &mut commands);
-            commands.entity(entity).insert(Breaker);
-            commands.entity(entity).remove::<Collider>();
-        };
+            WebEffect::Soak(mass) => {
+                web.wet(web_segment.spring_index, t, *mass);
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    };
 
     for collision_event in collision_events.read() {
         if let CollisionEvent::Started(entity_a, entity_b, _) = collision_event {
-            match (
-                obstacle_query.get(*entity_a),
-                obstacle_query.get(*entity_b),
+            let (entity, web_segment) = match (
+                obstacle_query.contains(*entity_a),
+                obstacle_query.contains(*entity_b),
                 web_segment_collisions_query.get(*entity_a),
                 web_segment_collisions_query.get(*entity_b),
             ) {
-                (Ok((_, trans)), Err(_), Ok(web_segment), Err(_)) => {
-                    handle_spring_break(&mut web, web_segment, trans.translation, *entity_a);
-                }
-                (Ok((_, trans)), Err(_), Err(_), Ok(web_segment)) => {
-                    handle_spring_break(&mut web, web_segment, trans.translation, *entity_a);
-                }
-                (Err(_), Ok((_, trans)), Ok(web_segment), Err(_)) => {
-                    handle_spring_break(&mut web, web_segment, trans.translation, *entity_b);
-                }
-                (Err(_), Ok((_, trans)), Err(_), Ok(web_segment)) => {
-                    handle_spring_break(&mut web, web_segment, trans.translation, *entity_b);
-                }
-                _ => {}
+                (true, false, Err(_), Ok(web_segment)) => (*entity_a, web_segment),
+                (false, true, Ok(web_segment), Err(_)) => (*entity_b, web_segment),
+                _ => continue,
+            };
+            // a drop soaks in once and a spent obstacle is done, whatever else they touched this
+            // frame, `Breaker` and losing the collider only apply after the system
+            if handled.contains(&entity) {
+                continue;
+            }
+            let Ok((mut obstacle, trans)) = obstacle_query.get_mut(entity) else {
+                continue;
+            };
+            handle_web_hit(
+                &mut web,
+                web_segment,
+                &mut obstacle,
+                trans.translation,
+                entity,
+            );
+            if matches!(
+                obstacle.effect,
+                WebEffect::Soak(_) | WebEffect::Push(_) | WebEffect::Cut(0)
+            ) {
+                handled.push(entity);
             }
         }
     }
 }
+
+/// lets the water raindrops left on the web drip off
+fn drip_web(mut web_query: Query<&mut WebSimulation>, time: Res<Time>) {
+    for mut web in &mut web_query {
+        web.drip(time.delta_seconds(), WATER_DRIP_RATE);
+    }
+}
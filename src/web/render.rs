@@ -1,64 +1,131 @@
-use super::Web;
-use crate::config::{COLLISION_GROUP_ENEMIES, COLLISION_GROUP_WALLS};
+use super::{SpringKey, WebSimulation};
+use crate::config::{GameConfig, COLLISION_GROUP_ENEMIES, COLLISION_GROUP_WALLS};
 use bevy::{
     log,
     prelude::*,
     render::{
-        mesh::{Indices, PrimitiveTopology},
+        mesh::{Indices, MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues},
         render_asset::RenderAssetUsages,
+        view::NoFrustumCulling,
     },
 };
 use bevy_rapier3d::geometry::{CollisionGroups, Group};
-use bevy_rapier3d::prelude::{ActiveCollisionTypes, ActiveEvents, Collider, Sensor};
-use std::{collections::HashMap, f32::consts::PI};
+use bevy_rapier3d::prelude::{ActiveCollisionTypes, ActiveEvents, Collider};
+use std::{collections::HashSet, f32::consts::PI};
 
-pub const WEB_SILK_THICKNESS: f32 = 0.03;
 pub const WEB_SILK_PRISM_BASE: i32 = 4;
 
+/// the one mesh entity all web silk is drawn with, updated in place every frame
 #[derive(Component)]
 pub struct WebRenderMesh {
     mesh_handle: Handle<Mesh>,
-    material_handle: Handle<StandardMaterial>,
+    /// springs the index buffer was last built for, in vertex order
+    springs: Vec<SpringKey>,
 }
 
 /// used only for collision
 #[derive(Component)]
 pub struct WebSegmentCollision {
-    pub spring_index: usize,
+    pub spring_index: SpringKey,
 }
 
-pub fn clear_web(
+pub fn spawn_web_render_mesh(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    web_render_segments_query: Query<(Entity, &WebRenderMesh)>,
-    web_segment_collisions_query: Query<(Entity, &WebSegmentCollision)>,
 ) {
-    for (
-        web_render_segment_entity,
+    let mesh_handle: Handle<Mesh> = meshes.add(
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, Vec::<[f32; 3]>::new())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, Vec::<[f32; 2]>::new())
+        .with_inserted_indices(Indices::U32(vec![])),
+    );
+
+    let material_handle: Handle<StandardMaterial> = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.8, 0.8, 0.8),
+        // unlit: true,
+        ..default()
+    });
+
+    commands.spawn((
+        PbrBundle {
+            mesh: mesh_handle.clone(),
+            material: material_handle,
+            ..default()
+        },
         WebRenderMesh {
-            mesh_handle: web_segment_mesh_handle,
-            material_handle: web_segment_material_handle,
+            mesh_handle,
+            springs: vec![],
         },
-    ) in web_render_segments_query.iter()
+        // the bounds are only worked out once, but the web keeps growing and moving
+        NoFrustumCulling,
+    ));
+}
+
+/// Keeps one capsule collider per spring. Colliders are only spawned for new springs and
+/// despawned for removed ones, the rest just follow their particles.
+pub fn sync_web_colliders(
+    mut commands: Commands,
+    web_query: Query<&WebSimulation>,
+    mut web_segment_collisions_query: Query<(Entity, &WebSegmentCollision, &mut Collider)>,
+    config: Res<GameConfig>,
+) {
+    let Ok(web_data) = web_query.get_single() else {
+        error!("ERROR NO WEB OR MORE THAN ONE WEB");
+        return;
+    };
+
+    let mut springs_with_collider: HashSet<SpringKey> = HashSet::new();
+
+    for (web_segment_collision_entity, web_segment_collision, mut collider) in
+        web_segment_collisions_query.iter_mut()
     {
-        meshes.remove(web_segment_mesh_handle);
-        materials.remove(web_segment_material_handle);
-        commands.entity(web_render_segment_entity).despawn();
+        let Some(spring) = web_data.springs.get(web_segment_collision.spring_index) else {
+            commands.entity(web_segment_collision_entity).despawn();
+            continue;
+        };
+
+        let first_position = web_data.particles[spring.first_index].position;
+        let second_position = web_data.particles[spring.second_index].position;
+        if let Some(mut capsule) = collider.as_capsule_mut() {
+            capsule.set_segment(first_position, second_position);
+            capsule.set_radius(config.silk_thickness / 2.0);
+        }
+        springs_with_collider.insert(web_segment_collision.spring_index);
     }
 
-    for (web_segment_collision_entity, _) in web_segment_collisions_query.iter() {
-        commands.entity(web_segment_collision_entity).despawn();
+    for (spring_index, spring) in web_data.springs.iter() {
+        if springs_with_collider.contains(&spring_index) {
+            continue;
+        }
+
+        let first_position = web_data.particles[spring.first_index].position;
+        let second_position = web_data.particles[spring.second_index].position;
+        commands
+            .spawn((
+                Collider::capsule(first_position, second_position, config.silk_thickness / 2.0),
+                WebSegmentCollision { spring_index },
+                TransformBundle::default(),
+            ))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC)
+            .insert(CollisionGroups {
+                memberships: COLLISION_GROUP_WALLS,
+                filters: Group::ALL,
+            });
     }
 }
 
 pub fn render_web(
-    mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    web_query: Query<&Web>,
+    web_query: Query<&WebSimulation>,
+    mut web_render_mesh_query: Query<&mut WebRenderMesh>,
     camera_query: Query<(&Transform, &Camera)>,
-    time: Res<Time>,
+    config: Res<GameConfig>,
 ) {
     let Ok(web_data) = web_query.get_single() else {
         error!("ERROR NO WEB OR MORE THAN ONE WEB");
@@ -70,177 +137,157 @@ pub fn render_web(
         return;
     };
 
-    let (mesh, segment_colliders) = create_web_mesh(&web_data, camera_transform);
-    let mesh_handle: Handle<Mesh> = meshes.add(mesh);
+    let Ok(mut web_render_mesh) = web_render_mesh_query.get_single_mut() else {
+        error!("ERROR NO WEB MESH OR MORE THAN ONE WEB MESH");
+        return;
+    };
 
-    let material_handle: Handle<StandardMaterial> = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.8, 0.8, 0.8),
-        // unlit: true,
-        ..default()
-    });
+    let Some(mesh) = meshes.get_mut(&web_render_mesh.mesh_handle) else {
+        error!("ERROR WEB MESH ASSET MISSING");
+        return;
+    };
 
-    for (segment_collider, spring_index) in segment_colliders {
-        commands
-            .spawn((segment_collider, WebSegmentCollision { spring_index }))
-            .insert(ActiveEvents::COLLISION_EVENTS)
-            .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC)
-            .insert(CollisionGroups {
-                memberships: COLLISION_GROUP_WALLS,
-                filters: Group::ALL,
-            });
+    let topology_changed = web_render_mesh.springs.len() != web_data.springs.len()
+        || web_render_mesh
+            .springs
+            .iter()
+            .zip(web_data.springs.keys())
+            .any(|(old, new)| *old != new);
+
+    if topology_changed {
+        web_render_mesh.springs.clear();
+        web_render_mesh.springs.extend(web_data.springs.keys());
+
+        let quad_count = web_render_mesh.springs.len() * quads_per_spring();
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_UV_0,
+            [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]].repeat(quad_count),
+        );
+        mesh.insert_indices(Indices::U32(create_web_indices(quad_count)));
     }
 
-    commands.spawn((
-        PbrBundle {
-            mesh: mesh_handle.clone(),
-            material: material_handle.clone(),
-            ..default()
-        },
-        WebRenderMesh {
-            mesh_handle: mesh_handle.clone(),
-            material_handle: material_handle.clone(),
-        },
-    ));
+    let mut positions = take_vec3_attribute(mesh, Mesh::ATTRIBUTE_POSITION);
+    let mut normals = take_vec3_attribute(mesh, Mesh::ATTRIBUTE_NORMAL);
+
+    for spring in web_data.springs.values() {
+        let first_position = web_data.particles[spring.first_index].position;
+        let second_position = web_data.particles[spring.second_index].position;
+        write_segment_vertices(
+            &mut positions,
+            &mut normals,
+            first_position,
+            second_position,
+            camera_transform,
+            config.silk_thickness,
+        );
+    }
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
 }
 
-fn create_web_mesh(web_data: &Web, camera_transform: &Transform) -> (Mesh, Vec<(Collider, usize)>) {
-    let mut positions: Vec<Vec3> = Vec::new();
-    let mut normals: Vec<Vec3> = Vec::new();
-    let mut uvs: Vec<Vec2> = Vec::new();
-
-    let mut indices: Vec<u32> = Vec::new();
-
-    let mut segment_colliders: Vec<(Collider, usize)> = Vec::new();
-
-    for (spring_index, spring) in web_data.springs.iter().enumerate() {
-        let first_index = spring.first_index;
-        let second_index = spring.second_index;
-        let first_position = web_data.particles[first_index].position;
-        let second_position = web_data.particles[second_index].position;
-        let center_position = (first_position + second_position) / 2.0;
-        let segment_as_vec = second_position - first_position;
-
-        segment_colliders.push((
-            Collider::capsule(first_position, second_position, WEB_SILK_THICKNESS / 2.0),
-            spring_index,
-        ));
-
-        if WEB_SILK_PRISM_BASE < 3 {
-            let to_camera = (camera_transform.translation - center_position).normalize();
-            let perp = segment_as_vec.cross(to_camera).normalize();
-            let top_left = first_position + perp * WEB_SILK_THICKNESS / 2.0;
-            let top_right = first_position - perp * WEB_SILK_THICKNESS / 2.0;
-
-            let bottom_left = second_position + perp * WEB_SILK_THICKNESS / 2.0;
-            let bottom_right = second_position - perp * WEB_SILK_THICKNESS / 2.0;
-
-            let top_left_index = positions.len();
-            let top_right_index = top_left_index + 1;
-            let bottom_left_index = top_left_index + 2;
-            let bottom_right_index = top_left_index + 3;
-
-            positions.push(top_left);
-            positions.push(top_right);
-            positions.push(bottom_left);
-            positions.push(bottom_right);
-
-            normals.push(to_camera);
-            normals.push(to_camera);
-            normals.push(to_camera);
-            normals.push(to_camera);
-
-            uvs.push(Vec2::new(0.0, 0.0));
-            uvs.push(Vec2::new(1.0, 0.0));
-            uvs.push(Vec2::new(0.0, 1.0));
-            uvs.push(Vec2::new(1.0, 1.0));
-
-            // triangle 1
-            indices.push(bottom_left_index.try_into().unwrap());
-            indices.push(top_right_index.try_into().unwrap());
-            indices.push(top_left_index.try_into().unwrap());
-
-            // triangle 2
-            indices.push(bottom_left_index.try_into().unwrap());
-            indices.push(bottom_right_index.try_into().unwrap());
-            indices.push(top_right_index.try_into().unwrap());
-            continue;
+/// takes a vertex attribute out of the mesh emptied, to be refilled without reallocating
+fn take_vec3_attribute(mesh: &mut Mesh, attribute: MeshVertexAttribute) -> Vec<[f32; 3]> {
+    match mesh.remove_attribute(attribute) {
+        Some(VertexAttributeValues::Float32x3(mut values)) => {
+            values.clear();
+            values
         }
+        _ => vec![],
+    }
+}
 
-        for i in 0..WEB_SILK_PRISM_BASE {
-            let quat = Quat::from_axis_angle(
-                segment_as_vec.normalize(),
-                i as f32 / WEB_SILK_PRISM_BASE as f32 * 2.0 * PI,
-            );
-            let normal = quat.mul_vec3(Vec3::new(0.0, 0.0, 1.0));
-
-            let top_left_index = positions.len();
-            let top_right_index = top_left_index + 1;
-            let bottom_left_index = top_left_index + 2;
-            let bottom_right_index = top_left_index + 3;
-
-            let perp = segment_as_vec.cross(normal).normalize();
-            let top_left = first_position
-                + perp * WEB_SILK_THICKNESS / 2.0
-                + normal * WEB_SILK_THICKNESS / 2.0;
-            let top_right = first_position - perp * WEB_SILK_THICKNESS / 2.0
-                + normal * WEB_SILK_THICKNESS / 2.0;
-
-            let bottom_left = second_position
-                + perp * WEB_SILK_THICKNESS / 2.0
-                + normal * WEB_SILK_THICKNESS / 2.0;
-            let bottom_right = second_position - perp * WEB_SILK_THICKNESS / 2.0
-                + normal * WEB_SILK_THICKNESS / 2.0;
-
-            positions.push(top_left);
-            positions.push(top_right);
-            positions.push(bottom_left);
-            positions.push(bottom_right);
-
-            normals.push(normal);
-            normals.push(normal);
-            normals.push(normal);
-            normals.push(normal);
-
-            uvs.push(Vec2::new(0.0, 0.0));
-            uvs.push(Vec2::new(1.0, 0.0));
-            uvs.push(Vec2::new(0.0, 1.0));
-            uvs.push(Vec2::new(1.0, 1.0));
-
-            // triangle 1
-            indices.push(bottom_left_index.try_into().unwrap());
-            indices.push(top_right_index.try_into().unwrap());
-            indices.push(top_left_index.try_into().unwrap());
-
-            // triangle 2
-            indices.push(bottom_left_index.try_into().unwrap());
-            indices.push(bottom_right_index.try_into().unwrap());
-            indices.push(top_right_index.try_into().unwrap());
-        }
+/// how many quads make up one strand of silk
+fn quads_per_spring() -> usize {
+    if WEB_SILK_PRISM_BASE < 3 {
+        1
+    } else {
+        WEB_SILK_PRISM_BASE as usize
     }
+}
 
-    let mesh = Mesh::new(
-        PrimitiveTopology::TriangleList,
-        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
-    )
-    .with_inserted_attribute(
-        Mesh::ATTRIBUTE_POSITION,
-        positions
-            .iter()
-            .map(|position| position.to_array())
-            .collect::<Vec<_>>(),
-    )
-    .with_inserted_attribute(
-        Mesh::ATTRIBUTE_NORMAL,
-        normals
-            .iter()
-            .map(|normal| normal.to_array())
-            .collect::<Vec<_>>(),
-    )
-    .with_inserted_attribute(
-        Mesh::ATTRIBUTE_UV_0,
-        uvs.iter().map(|uv| uv.to_array()).collect::<Vec<_>>(),
-    )
-    .with_inserted_indices(Indices::U32(indices));
-
-    (mesh, segment_colliders)
+/// two triangles per quad, every quad has its own four vertices
+fn create_web_indices(quad_count: usize) -> Vec<u32> {
+    let mut indices: Vec<u32> = Vec::with_capacity(quad_count * 6);
+
+    for quad in 0..quad_count {
+        let top_left_index: u32 = (quad * 4).try_into().unwrap();
+        let top_right_index = top_left_index + 1;
+        let bottom_left_index = top_left_index + 2;
+        let bottom_right_index = top_left_index + 3;
+
+        // triangle 1
+        indices.push(bottom_left_index);
+        indices.push(top_right_index);
+        indices.push(top_left_index);
+
+        // triangle 2
+        indices.push(bottom_left_index);
+        indices.push(bottom_right_index);
+        indices.push(top_right_index);
+    }
+
+    indices
+}
+
+fn push_quad(
+    positions: &mut Vec<[f32; 3]>,
+    normals: &mut Vec<[f32; 3]>,
+    corners: [Vec3; 4],
+    normal: Vec3,
+) {
+    positions.extend(corners.map(|corner| corner.to_array()));
+    normals.extend([normal.to_array(); 4]);
+}
+
+fn write_segment_vertices(
+    positions: &mut Vec<[f32; 3]>,
+    normals: &mut Vec<[f32; 3]>,
+    first_position: Vec3,
+    second_position: Vec3,
+    camera_transform: &Transform,
+    thickness: f32,
+) {
+    let center_position = (first_position + second_position) / 2.0;
+    let segment_as_vec = second_position - first_position;
+
+    if WEB_SILK_PRISM_BASE < 3 {
+        let to_camera = (camera_transform.translation - center_position).normalize();
+        let perp = segment_as_vec.cross(to_camera).normalize();
+        let top_left = first_position + perp * thickness / 2.0;
+        let top_right = first_position - perp * thickness / 2.0;
+
+        let bottom_left = second_position + perp * thickness / 2.0;
+        let bottom_right = second_position - perp * thickness / 2.0;
+
+        push_quad(
+            positions,
+            normals,
+            [top_left, top_right, bottom_left, bottom_right],
+            to_camera,
+        );
+        return;
+    }
+
+    for i in 0..WEB_SILK_PRISM_BASE {
+        let quat = Quat::from_axis_angle(
+            segment_as_vec.normalize(),
+            i as f32 / WEB_SILK_PRISM_BASE as f32 * 2.0 * PI,
+        );
+        let normal = quat.mul_vec3(Vec3::new(0.0, 0.0, 1.0));
+
+        let perp = segment_as_vec.cross(normal).normalize();
+        let top_left = first_position + perp * thickness / 2.0 + normal * thickness / 2.0;
+        let top_right = first_position - perp * thickness / 2.0 + normal * thickness / 2.0;
+
+        let bottom_left = second_position + perp * thickness / 2.0 + normal * thickness / 2.0;
+        let bottom_right = second_position - perp * thickness / 2.0 + normal * thickness / 2.0;
+
+        push_quad(
+            positions,
+            normals,
+            [top_left, top_right, bottom_left, bottom_right],
+            normal,
+        );
+    }
 }
@@ -0,0 +1,359 @@
+use super::ensnare::{release_insect, Ensnared, EnsnaredEntity};
+use super::integrator::Integrator;
+use super::spring::Spring;
+use super::{Particle, ParticleKey, SpringKey, Web, WebSimulation};
+use crate::config::GameConfig;
+use crate::controls::{ControlAction, ControlState};
+use crate::flying_insect::flying_insect::{insect_base_rotation, FlyingInsect};
+use crate::flying_insect::spawn::spawn_insect_entity;
+use crate::spider::Spider;
+use bevy::prelude::*;
+use ld56_sim::flight_path::{ArcLengthPath, Polyline};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Bumped whenever the layout of [`WebFile`] changes.
+pub const WEB_FILE_VERSION: u32 = 4;
+
+/// Where [`ControlAction::SaveWeb`] and [`ControlAction::LoadWeb`] save and load the web.
+pub const WEB_SAVE_PATH: &str = "web.ron";
+
+/// On disk form of a [`Web`]. Particles are stored in a list and springs refer to them by their
+/// position in it, so files don't depend on slotmap keys. Ensnared insects are stored by species
+/// and spawned again on load, since their entities only live as long as the session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebFile {
+    pub version: u32,
+    pub mass_per_unit_length: f32,
+    pub integrator: Integrator,
+    pub particles: Vec<ParticleFile>,
+    pub springs: Vec<SpringFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticleFile {
+    pub position: [f32; 3],
+    pub velocity: [f32; 3],
+    pub mass: f32,
+    pub pinned: bool,
+    /// water sitting on the particle, missing before version 3
+    #[serde(default)]
+    pub water: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpringFile {
+    /// index into [`WebFile::particles`]
+    pub first: usize,
+    /// index into [`WebFile::particles`]
+    pub second: usize,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub rest_length: f32,
+    pub max_strain: f32,
+    /// missing before version 4, version 1 files had entity ids here which are ignored
+    #[serde(default)]
+    pub ensnared: Vec<EnsnaredFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnsnaredFile {
+    /// name of its `InsectSpecies`, empty in version 1 files which are skipped
+    #[serde(default)]
+    pub species: String,
+    pub snare_position: f32,
+    pub mass: f32,
+    pub rotation: f32,
+    /// whether the spider had wrapped it up
+    pub done_ensnaring: bool,
+}
+
+#[derive(Debug)]
+pub enum WebFileError {
+    Io(std::io::Error),
+    Serialize(ron::Error),
+    Parse(ron::error::SpannedError),
+    UnsupportedVersion(u32),
+    InvalidParticleIndex(usize),
+}
+
+impl std::fmt::Display for WebFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebFileError::Io(err) => write!(f, "{}", err),
+            WebFileError::Serialize(err) => write!(f, "{}", err),
+            WebFileError::Parse(err) => write!(f, "{}", err),
+            WebFileError::UnsupportedVersion(version) => write!(
+                f,
+                "web file version {} is newer than {}",
+                version, WEB_FILE_VERSION
+            ),
+            WebFileError::InvalidParticleIndex(index) => {
+                write!(f, "spring refers to missing particle {}", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WebFileError {}
+
+impl WebFile {
+    /// `species_of` names the species of an ensnared insect, insects it doesn't know are left out
+    pub fn from_web(web: &Web, species_of: impl Fn(Entity) -> Option<String>) -> Self {
+        let mut particle_indices: HashMap<ParticleKey, usize> = HashMap::new();
+        let mut particles = Vec::with_capacity(web.particles.len());
+        for (key, particle) in web.particles.iter() {
+            particle_indices.insert(key, particles.len());
+            particles.push(ParticleFile {
+                position: particle.position.to_array(),
+                velocity: particle.velocity.to_array(),
+                mass: particle.mass,
+                pinned: particle.pinned,
+                water: web.water_at(key),
+            });
+        }
+
+        let springs = web
+            .springs
+            .values()
+            .map(|spring| SpringFile {
+                first: particle_indices[&spring.first_index],
+                second: particle_indices[&spring.second_index],
+                stiffness: spring.stiffness,
+                damping: spring.damping,
+                rest_length: spring.rest_length,
+                max_strain: spring.max_strain,
+                ensnared: spring
+                    .ensnared_entities
+                    .iter()
+                    .filter_map(|ensnared| {
+                        Some(EnsnaredFile {
+                            species: species_of(ensnared.entity)?,
+                            snare_position: ensnared.snare_position,
+                            mass: ensnared.mass,
+                            rotation: ensnared.rotation,
+                            done_ensnaring: ensnared.done_ensnaring,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        WebFile {
+            version: WEB_FILE_VERSION,
+            mass_per_unit_length: web.mass_per_unit_length,
+            integrator: web.integrator,
+            particles,
+            springs,
+        }
+    }
+
+    pub fn to_ron(&self) -> Result<String, WebFileError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(WebFileError::Serialize)
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, WebFileError> {
+        let file: WebFile = ron::from_str(text).map_err(WebFileError::Parse)?;
+        if file.version > WEB_FILE_VERSION {
+            return Err(WebFileError::UnsupportedVersion(file.version));
+        }
+        Ok(file)
+    }
+
+    /// Builds the web back up, with nothing ensnared in it yet. The insects that were are
+    /// returned with the spring they belong on, to be spawned and put back.
+    #[allow(clippy::type_complexity)]
+    pub fn into_web(self) -> Result<(Web, Vec<(SpringKey, EnsnaredFile)>), WebFileError> {
+        let mut web = Web {
+            mass_per_unit_length: self.mass_per_unit_length,
+            integrator: self.integrator,
+            // the file may hold loose fragments
+            needs_compact: true,
+            ..Default::default()
+        };
+
+        let particle_keys: Vec<ParticleKey> = self
+            .particles
+            .into_iter()
+            .map(|particle| {
+                let key = web.particles.insert(Particle {
+                    position: Vec3::from_array(particle.position),
+                    velocity: Vec3::from_array(particle.velocity),
+                    force: Vec3::ZERO,
+                    impulse: Vec3::ZERO,
+                    impulse_duration: 0.0,
+                    mass: particle.mass,
+                    pinned: particle.pinned,
+                });
+                if particle.water > 0.0 {
+                    web.water.insert(key, particle.water);
+                }
+                key
+            })
+            .collect();
+
+        let mut ensnared = vec![];
+        for spring in self.springs {
+            let (Some(&first_index), Some(&second_index)) = (
+                particle_keys.get(spring.first),
+                particle_keys.get(spring.second),
+            ) else {
+                return Err(WebFileError::InvalidParticleIndex(
+                    spring.first.max(spring.second),
+                ));
+            };
+
+            let key = web.springs.insert(Spring {
+                first_index,
+                second_index,
+                stiffness: spring.stiffness,
+                damping: spring.damping,
+                rest_length: spring.rest_length,
+                max_strain: spring.max_strain,
+                ensnared_entities: vec![],
+            });
+            ensnared.extend(
+                spring
+                    .ensnared
+                    .into_iter()
+                    .filter(|insect| !insect.species.is_empty())
+                    .map(|insect| (key, insect)),
+            );
+        }
+
+        Ok((web, ensnared))
+    }
+}
+
+pub fn save_web_to_file(
+    web: &Web,
+    species_of: impl Fn(Entity) -> Option<String>,
+    path: impl AsRef<Path>,
+) -> Result<(), WebFileError> {
+    let text = WebFile::from_web(web, species_of).to_ron()?;
+    std::fs::write(path, text).map_err(WebFileError::Io)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn load_web_from_file(
+    path: impl AsRef<Path>,
+) -> Result<(Web, Vec<(SpringKey, EnsnaredFile)>), WebFileError> {
+    let text = std::fs::read_to_string(path).map_err(WebFileError::Io)?;
+    WebFile::from_ron(&text)?.into_web()
+}
+
+/// Spawns a saved insect of a species from the config and catches it on `spring`
+fn respawn_ensnared_insect(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    config: &GameConfig,
+    web: &mut Web,
+    spring_index: SpringKey,
+    saved: EnsnaredFile,
+) {
+    let Some(species) = config
+        .insect_species
+        .iter()
+        .find(|s| s.name == saved.species)
+    else {
+        error!("No insect species called {}", saved.species);
+        return;
+    };
+    let spring = &web.springs[spring_index];
+    let position = web.particles[spring.first_index].position.lerp(
+        web.particles[spring.second_index].position,
+        saved.snare_position,
+    );
+
+    let path = || ArcLengthPath::new(Polyline::new(vec![position]));
+    let entity = spawn_insect_entity(
+        commands,
+        asset_server,
+        species,
+        path(),
+        position,
+        insect_base_rotation(),
+    );
+    commands.entity(entity).insert(Ensnared);
+    if saved.done_ensnaring {
+        // the insect keeps track of being wrapped up as well
+        let mut insect = FlyingInsect::new(species, path());
+        insect.snare_roll_progress = 1.0;
+        commands.entity(entity).insert(insect);
+    }
+
+    web.springs[spring_index]
+        .ensnared_entities
+        .push(EnsnaredEntity {
+            entity,
+            snare_position: saved.snare_position,
+            mass: saved.mass,
+            rotation: saved.rotation,
+            lerp_rotation: saved.rotation,
+            done_ensnaring: saved.done_ensnaring,
+        });
+}
+
+/// Saves the web, or swaps it for the saved one. Loading lets go of every insect in the old web,
+/// spawns the saved ones, and sends the spider back to the tree, since its position and route
+/// point into the old web.
+#[allow(clippy::too_many_arguments)]
+pub fn save_load_web(
+    mut commands: Commands,
+    mut web_query: Query<&mut WebSimulation>,
+    mut spider_query: Query<&mut Spider>,
+    insect_query: Query<&FlyingInsect>,
+    controls: Res<ControlState>,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
+    let Ok(mut web) = web_query.get_single_mut() else {
+        return;
+    };
+
+    if controls.just_pressed(ControlAction::SaveWeb) {
+        let species_of = |entity| insect_query.get(entity).ok().map(|i| i.species.clone());
+        match save_web_to_file(&web, species_of, WEB_SAVE_PATH) {
+            Ok(()) => info!("Saved web to {}", WEB_SAVE_PATH),
+            Err(err) => error!("Could not save web to {}: {}", WEB_SAVE_PATH, err),
+        }
+    }
+
+    if controls.just_pressed(ControlAction::LoadWeb) {
+        let (loaded, ensnared) = match load_web_from_file(WEB_SAVE_PATH) {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                error!("Could not load web from {}: {}", WEB_SAVE_PATH, err);
+                return;
+            }
+        };
+
+        for spring in web.springs.values() {
+            for ensnared in &spring.ensnared_entities {
+                release_insect(
+                    &mut commands,
+                    ensnared.entity,
+                    insect_query.get(ensnared.entity).ok(),
+                );
+            }
+        }
+        **web = loaded;
+        for (spring_index, saved) in ensnared {
+            respawn_ensnared_insect(
+                &mut commands,
+                &asset_server,
+                &config,
+                &mut web,
+                spring_index,
+                saved,
+            );
+        }
+
+        for mut spider in spider_query.iter_mut() {
+            spider.return_to_start();
+        }
+        info!("Loaded web from {}", WEB_SAVE_PATH);
+    }
+}